@@ -1,7 +1,7 @@
 use crate::config::JwtConfig;
 use crate::error::AppError;
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRequestParts, OptionalFromRequestParts},
     http::{StatusCode, request::Parts},
     response::{IntoResponse, Response},
 };
@@ -63,6 +63,30 @@ where
     }
 }
 
+/// `Option<AuthUser>` is `None` without an `Authorization` header; a header carrying a bad
+/// token is still rejected.
+impl<S> OptionalFromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts
+            .headers
+            .contains_key(axum::http::header::AUTHORIZATION)
+        {
+            return Ok(None);
+        }
+        <AuthUser as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct AdminUser(pub AuthUser);
@@ -74,7 +98,7 @@ where
     type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+        let auth_user = <AuthUser as FromRequestParts<S>>::from_request_parts(parts, state).await?;
         if !auth_user.is_super_admin {
             return Err((StatusCode::FORBIDDEN, "admin access required").into_response());
         }
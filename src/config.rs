@@ -12,6 +12,19 @@ const DEFAULT_POOL_MAX_SIZE: usize = 20;
 const DEFAULT_CONNECTION_TIMEOUT_SECS: u64 = 30;
 const DEFAULT_JWT_SECRET: &str = "your-secret-key";
 const DEFAULT_JWT_EXPIRY_HOURS: i64 = 3;
+const DEFAULT_PUBLISH_JOB_INTERVAL_SECS: u64 = 30;
+const DEFAULT_TRENDING_JOB_INTERVAL_SECS: u64 = 600;
+const DEFAULT_TRENDING_GRAVITY: f64 = 1.8;
+const DEFAULT_TRASH_PURGE_JOB_INTERVAL_SECS: u64 = 3600;
+const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+const DEFAULT_SITE_BASE_URL: &str = "http://localhost:3000";
+const DEFAULT_SITE_TITLE: &str = "Blog";
+const DEFAULT_SITE_DESCRIPTION: &str = "Latest posts";
+const DEFAULT_VIEW_DEDUP_WINDOW_MINUTES: u64 = 30;
+const DEFAULT_TRUSTED_PROXY_HOPS: usize = 0;
+const DEFAULT_STORAGE_LOCAL_DIR: &str = "./uploads";
+const DEFAULT_S3_REGION: &str = "us-east-1";
+const DEFAULT_UPLOAD_MAX_BYTES: usize = 10 * 1024 * 1024;
 
 // ============================================================================
 // Configuration Structures
@@ -24,6 +37,10 @@ pub struct Config {
     pub database_url: String,
     pub db_pool: PoolConfig,
     pub jwt: JwtConfig,
+    pub jobs: JobsConfig,
+    pub site: SiteConfig,
+    pub views: ViewConfig,
+    pub storage: StorageConfig,
 }
 
 /// Database connection pool configuration
@@ -40,7 +57,65 @@ pub struct JwtConfig {
     pub expiry_hours: i64,
 }
 
+/// Background job configuration
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    pub publish_interval: Duration,
+    pub trending_interval: Duration,
+    /// Exponent applied to post age when ranking trending posts; higher favours newer posts.
+    pub trending_gravity: f64,
+    pub trash_purge_interval: Duration,
+    /// How long soft-deleted posts stay in the trash before they are purged.
+    pub trash_retention: chrono::Duration,
+}
+
+/// Public site details used to build absolute links in feeds
+#[derive(Debug, Clone)]
+pub struct SiteConfig {
+    pub base_url: String,
+    pub title: String,
+    pub description: String,
+}
+
+/// Post view counting configuration
+#[derive(Debug, Clone)]
+pub struct ViewConfig {
+    /// Repeat anonymous views from the same IP and user agent within this window count once.
+    pub dedup_window: Duration,
+    /// Reverse proxies in front of the API that append to `X-Forwarded-For`. With none, the
+    /// connecting address is the client.
+    pub trusted_proxy_hops: usize,
+}
+
+/// Where uploaded files are kept
+#[derive(Debug, Clone)]
+pub enum StorageBackend {
+    Local {
+        root: String,
+    },
+    /// Any S3-compatible service; set `endpoint` for MinIO and similar stand-ins.
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        access_key_id: Option<String>,
+        secret_access_key: Option<String>,
+    },
+}
+
+/// Upload storage configuration
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// Base URL files are publicly served from (e.g. a CDN or bucket URL). When unset,
+    /// files are served by the API under `/api/uploads/files/`.
+    pub public_base_url: Option<String>,
+    pub max_upload_bytes: usize,
+}
+
 static JWT_CONFIG: OnceLock<JwtConfig> = OnceLock::new();
+static SITE_CONFIG: OnceLock<SiteConfig> = OnceLock::new();
+static VIEW_CONFIG: OnceLock<ViewConfig> = OnceLock::new();
 
 impl JwtConfig {
     fn from_env() -> Self {
@@ -59,6 +134,85 @@ impl JwtConfig {
     }
 }
 
+impl SiteConfig {
+    fn from_env() -> Self {
+        Self {
+            base_url: env::var("SITE_BASE_URL")
+                .unwrap_or_else(|_| DEFAULT_SITE_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            title: env::var("SITE_TITLE").unwrap_or_else(|_| DEFAULT_SITE_TITLE.to_string()),
+            description: env::var("SITE_DESCRIPTION")
+                .unwrap_or_else(|_| DEFAULT_SITE_DESCRIPTION.to_string()),
+        }
+    }
+
+    pub fn init(cfg: SiteConfig) {
+        SITE_CONFIG
+            .set(cfg)
+            .expect("SiteConfig already initialized");
+    }
+
+    pub fn get() -> &'static SiteConfig {
+        SITE_CONFIG.get().expect("SiteConfig not initialized")
+    }
+
+    /// Absolute URL for a site path such as `/tags/rust`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+impl ViewConfig {
+    fn from_env() -> Self {
+        Self {
+            dedup_window: Duration::from_secs(
+                parse_u64(
+                    "VIEW_DEDUP_WINDOW_MINUTES",
+                    DEFAULT_VIEW_DEDUP_WINDOW_MINUTES,
+                ) * 60,
+            ),
+            trusted_proxy_hops: parse_usize("TRUSTED_PROXY_HOPS", DEFAULT_TRUSTED_PROXY_HOPS),
+        }
+    }
+
+    pub fn init(cfg: ViewConfig) {
+        VIEW_CONFIG
+            .set(cfg)
+            .expect("ViewConfig already initialized");
+    }
+
+    pub fn get() -> &'static ViewConfig {
+        VIEW_CONFIG.get().expect("ViewConfig not initialized")
+    }
+}
+
+impl StorageConfig {
+    fn from_env() -> Self {
+        let backend = match env::var("STORAGE_BACKEND").as_deref() {
+            Ok("s3") => StorageBackend::S3 {
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET is required for the s3 backend"),
+                region: env::var("S3_REGION").unwrap_or_else(|_| DEFAULT_S3_REGION.to_string()),
+                endpoint: env::var("S3_ENDPOINT").ok(),
+                access_key_id: env::var("S3_ACCESS_KEY_ID").ok(),
+                secret_access_key: env::var("S3_SECRET_ACCESS_KEY").ok(),
+            },
+            Ok("local") | Err(_) => StorageBackend::Local {
+                root: env::var("STORAGE_LOCAL_DIR")
+                    .unwrap_or_else(|_| DEFAULT_STORAGE_LOCAL_DIR.to_string()),
+            },
+            Ok(other) => panic!("STORAGE_BACKEND must be \"local\" or \"s3\", got \"{other}\""),
+        };
+        Self {
+            backend,
+            public_base_url: env::var("STORAGE_PUBLIC_BASE_URL")
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            max_upload_bytes: parse_usize("UPLOAD_MAX_BYTES", DEFAULT_UPLOAD_MAX_BYTES),
+        }
+    }
+}
+
 // ============================================================================
 // Implementation
 // ============================================================================
@@ -75,6 +229,22 @@ impl Config {
     /// - `DB_POOL_IDLE_TIMEOUT`: Idle timeout in seconds, 0 = no limit (default: 600)
     /// - `JWT_SECRET`: Secret key for signing JWT tokens (default: "your-secret-key")
     /// - `JWT_EXPIRY_HOURS`: Access token expiry in hours (default: 3)
+    /// - `PUBLISH_JOB_INTERVAL_SECS`: How often scheduled posts are checked (default: 30)
+    /// - `TRENDING_JOB_INTERVAL_SECS`: How often trending rankings are rebuilt (default: 600)
+    /// - `TRENDING_GRAVITY`: Age decay exponent for trending scores (default: 1.8)
+    /// - `TRASH_PURGE_JOB_INTERVAL_SECS`: How often expired trash is purged (default: 3600)
+    /// - `TRASH_RETENTION_DAYS`: Days a deleted post stays restorable (default: 30)
+    /// - `SITE_BASE_URL`: Public site URL used for absolute links (default: "http://localhost:3000")
+    /// - `SITE_TITLE`: Site name shown in feeds (default: "Blog")
+    /// - `SITE_DESCRIPTION`: Site description shown in feeds (default: "Latest posts")
+    /// - `VIEW_DEDUP_WINDOW_MINUTES`: Window for counting repeat anonymous views once (default: 30)
+    /// - `TRUSTED_PROXY_HOPS`: Proxies whose `X-Forwarded-For` entries identify viewers (default: 0)
+    /// - `STORAGE_BACKEND`: Upload storage, "local" or "s3" (default: "local")
+    /// - `STORAGE_LOCAL_DIR`: Directory for the local backend (default: "./uploads")
+    /// - `S3_BUCKET`, `S3_REGION`, `S3_ENDPOINT`, `S3_ACCESS_KEY_ID`, `S3_SECRET_ACCESS_KEY`:
+    ///   S3 backend settings; `S3_ENDPOINT` points at MinIO-style stand-ins (region default: "us-east-1")
+    /// - `STORAGE_PUBLIC_BASE_URL`: Public base URL for stored files (default: served by the API)
+    /// - `UPLOAD_MAX_BYTES`: Maximum upload size in bytes (default: 10485760)
     ///
     /// # Panics
     /// Panics if numeric values cannot be parsed.
@@ -85,6 +255,10 @@ impl Config {
                 .unwrap_or_else(|_| DEFAULT_DATABASE_URL.to_string()),
             db_pool: PoolConfig::from_env(),
             jwt: JwtConfig::from_env(),
+            jobs: JobsConfig::from_env(),
+            site: SiteConfig::from_env(),
+            views: ViewConfig::from_env(),
+            storage: StorageConfig::from_env(),
         }
     }
 }
@@ -101,6 +275,39 @@ impl PoolConfig {
     }
 }
 
+impl JobsConfig {
+    fn from_env() -> Self {
+        Self {
+            publish_interval: Duration::from_secs(
+                parse_u64(
+                    "PUBLISH_JOB_INTERVAL_SECS",
+                    DEFAULT_PUBLISH_JOB_INTERVAL_SECS,
+                )
+                .max(1),
+            ),
+            trending_interval: Duration::from_secs(
+                parse_u64(
+                    "TRENDING_JOB_INTERVAL_SECS",
+                    DEFAULT_TRENDING_JOB_INTERVAL_SECS,
+                )
+                .max(1),
+            ),
+            trending_gravity: parse_f64("TRENDING_GRAVITY", DEFAULT_TRENDING_GRAVITY),
+            trash_purge_interval: Duration::from_secs(
+                parse_u64(
+                    "TRASH_PURGE_JOB_INTERVAL_SECS",
+                    DEFAULT_TRASH_PURGE_JOB_INTERVAL_SECS,
+                )
+                .max(1),
+            ),
+            trash_retention: chrono::Duration::days(parse_u64(
+                "TRASH_RETENTION_DAYS",
+                DEFAULT_TRASH_RETENTION_DAYS,
+            ) as i64),
+        }
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -136,3 +343,11 @@ fn parse_i64(key: &str, default: i64) -> i64 {
         .parse::<i64>()
         .unwrap_or_else(|_| panic!("{key} must be a valid i64 number"))
 }
+
+/// Parse an environment variable as f64 with default fallback.
+fn parse_f64(key: &str, default: f64) -> f64 {
+    env::var(key)
+        .unwrap_or_else(|_| default.to_string())
+        .parse::<f64>()
+        .unwrap_or_else(|_| panic!("{key} must be a valid number"))
+}
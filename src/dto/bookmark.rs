@@ -56,5 +56,7 @@ pub struct BookmarkQuery {
     pub limit: Option<i64>,
     #[validate(range(min = 0, max = 10_000))]
     pub offset: Option<i64>,
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
     pub folder_id: Option<String>,
 }
@@ -1,3 +1,6 @@
+use crate::services::comment::CommentSort;
+use crate::services::comment_moderation::ModerationAction;
+use crate::services::comment_reaction::Reaction;
 use serde::Deserialize;
 use uuid::Uuid;
 use validator::Validate;
@@ -8,8 +11,64 @@ pub struct CommentRequest {
     pub text: String,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub text: String,
+    /// Set to reply to another comment on the same post.
+    pub parent_comment_id: Option<Uuid>,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct CommentPath {
     pub id: Uuid,
     pub comment_id: Uuid,
 }
+
+/// `CursorPaginationQuery` plus the thread order and a share token; cursors only work with
+/// `sort=newest`.
+#[derive(Deserialize, Validate)]
+pub struct CommentListQuery {
+    #[validate(range(min = 0, max = 10_000))]
+    pub offset: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub sort: CommentSort,
+    #[validate(length(max = 128))]
+    pub share_token: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CommentReactionPath {
+    pub id: Uuid,
+    pub comment_id: Uuid,
+    pub reaction: Reaction,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ModerateCommentRequest {
+    pub action: ModerationAction,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+/// Fields left out keep their current value.
+#[derive(Deserialize, Validate)]
+pub struct CommentSettingsRequest {
+    pub comments_locked: Option<bool>,
+    pub comment_approval_required: Option<bool>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ModerationQueueQuery {
+    pub post_id: Option<Uuid>,
+    #[validate(range(min = 0, max = 10_000))]
+    pub offset: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+}
@@ -11,6 +11,17 @@ pub struct PaginationQuery {
     pub limit: Option<i64>,
 }
 
+/// `PaginationQuery` for listings that can also be paged by `next_cursor`.
+#[derive(Deserialize, Validate)]
+pub struct CursorPaginationQuery {
+    #[validate(range(min = 0, max = 10_000))]
+    pub offset: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct UsernamePath {
     #[validate(length(min = 1, max = 50), regex(path = *USERNAME_RE))]
@@ -21,3 +32,10 @@ pub struct UsernamePath {
 pub struct PostIdPath {
     pub id: Uuid,
 }
+
+/// For endpoints hanging off a post: the secret from a share link opens private posts.
+#[derive(Deserialize, Validate)]
+pub struct ShareTokenQuery {
+    #[validate(length(max = 128))]
+    pub share_token: Option<String>,
+}
@@ -0,0 +1,41 @@
+use crate::dto::validation::{TAG_RE, USERNAME_RE};
+use crate::services;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl From<FeedFormat> for services::feed::FeedFormat {
+    fn from(value: FeedFormat) -> Self {
+        match value {
+            FeedFormat::Rss => Self::Rss,
+            FeedFormat::Atom => Self::Atom,
+            FeedFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SiteFeedPath {
+    pub format: FeedFormat,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct AuthorFeedPath {
+    #[validate(length(min = 1, max = 50), regex(path = *USERNAME_RE))]
+    pub username: String,
+    pub format: FeedFormat,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct TagFeedPath {
+    #[validate(length(min = 1, max = 50), regex(path = *TAG_RE))]
+    pub tag: String,
+    pub format: FeedFormat,
+}
@@ -1,11 +1,18 @@
 use serde::Deserialize;
-use validator::Validate;
+use std::collections::HashMap;
+use validator::{Validate, ValidationError};
 
 #[derive(Deserialize, Validate)]
 pub struct HoldingPath {
     pub id: i64,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct HoldingHistoryPath {
+    pub id: i64,
+    pub audit_id: i64,
+}
+
 #[derive(Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct HoldingQuery {
@@ -113,3 +120,63 @@ pub struct MonthlyQuery {
     pub end_month: Option<i32>,
     pub end_year: Option<i32>,
 }
+
+#[derive(Deserialize, Validate)]
+pub struct PositionHistoryQuery {
+    #[validate(length(min = 1, max = 50))]
+    pub symbol: Option<String>,
+    #[validate(length(min = 1))]
+    pub name: Option<String>,
+    #[validate(length(min = 1))]
+    pub platform: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectionMode {
+    #[default]
+    Deterministic,
+    MonteCarlo,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectionRequest {
+    #[validate(range(min = 1, max = 12))]
+    pub month: Option<i32>,
+    #[validate(range(min = 2000))]
+    pub year: Option<i32>,
+    #[validate(range(min = 1, max = 50))]
+    pub years: u32,
+    #[serde(default)]
+    #[validate(range(min = 0.0))]
+    pub monthly_contribution: f64,
+    /// Expected annual return in percent, keyed by holding type name or code.
+    #[serde(default)]
+    #[validate(custom(function = "validate_expected_returns"))]
+    pub expected_returns: HashMap<String, f64>,
+    /// Annual return in percent for holding types missing from `expected_returns`.
+    #[validate(range(min = -100.0, max = 100.0))]
+    pub default_return: Option<f64>,
+    #[serde(default)]
+    #[validate(range(min = -20.0, max = 100.0))]
+    pub inflation: f64,
+    #[serde(default)]
+    pub mode: ProjectionMode,
+    #[validate(range(min = 100, max = 10_000))]
+    pub simulations: Option<u32>,
+    pub seed: Option<u64>,
+}
+
+/// Each expected return gets the same -100..=100 range as `default_return`.
+fn validate_expected_returns(returns: &HashMap<String, f64>) -> Result<(), ValidationError> {
+    if returns
+        .values()
+        .all(|value| (-100.0..=100.0).contains(value))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("range")
+            .with_message("expected returns must be between -100 and 100".into()))
+    }
+}
@@ -2,10 +2,15 @@ pub mod auth;
 pub mod bookmark;
 pub mod comment;
 pub mod common;
+pub mod feed;
 pub mod holding;
 pub mod notification;
 pub mod post;
 pub mod report;
+pub mod series;
+pub mod sitemap;
 pub mod tag;
+pub mod timeline;
+pub mod upload;
 pub mod user;
 pub mod validation;
@@ -10,6 +10,8 @@ pub struct NotificationQuery {
     pub limit: Option<i64>,
     #[validate(range(min = 0, max = 10_000))]
     pub offset: Option<i64>,
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
 }
 
 #[derive(Deserialize, Validate)]
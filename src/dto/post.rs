@@ -1,6 +1,8 @@
 use crate::dto::validation::{SLUG_RE, TAG_RE, USERNAME_RE};
-use crate::services;
+use crate::services::{self, visibility::PostVisibility};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use uuid::Uuid;
 use validator::Validate;
 
 #[derive(Deserialize, Validate)]
@@ -14,6 +16,10 @@ pub struct CreatePostRequest {
     pub body: String,
     #[serde(default)]
     pub published: bool,
+    /// Publish later: the post stays out of listings until this time passes.
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub visibility: PostVisibility,
     #[serde(default)]
     pub tags: Vec<String>,
 }
@@ -28,6 +34,8 @@ pub struct UpdatePostRequest {
     #[validate(length(min = 1))]
     pub body: Option<String>,
     pub published: Option<bool>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub visibility: Option<PostVisibility>,
     pub tags: Option<Vec<String>>,
 }
 
@@ -37,6 +45,14 @@ pub struct RandomPostQuery {
     pub limit: Option<i64>,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct TrendingPostQuery {
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// `24h`, `7d` (default) or `30d`.
+    pub window: Option<services::trending::TrendingWindow>,
+}
+
 #[derive(Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum OrderDirection {
@@ -60,6 +76,9 @@ pub struct PostPaginationQuery {
     pub offset: Option<i64>,
     #[validate(range(min = 1, max = 100))]
     pub limit: Option<i64>,
+    /// `next_cursor` from the previous page; replaces `offset`.
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
     #[validate(length(max = 200))]
     pub search: Option<String>,
     #[serde(alias = "sort_by")]
@@ -68,6 +87,53 @@ pub struct PostPaginationQuery {
     pub order_direction: Option<OrderDirection>,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct PostDetailQuery {
+    /// Include `rendered` (sanitized HTML, table of contents, reading time) in the response.
+    #[serde(default)]
+    pub render: bool,
+    /// Secret from a share link; opens private posts.
+    #[validate(length(max = 128))]
+    pub share_token: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ShareTokenPath {
+    pub id: Uuid,
+    pub token_id: Uuid,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct PostRevisionPath {
+    pub id: Uuid,
+    pub revision_id: i64,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffGranularity {
+    #[default]
+    Line,
+    Word,
+}
+
+impl From<DiffGranularity> for services::post_revision::DiffGranularity {
+    fn from(value: DiffGranularity) -> Self {
+        match value {
+            DiffGranularity::Line => Self::Line,
+            DiffGranularity::Word => Self::Word,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RevisionDiffQuery {
+    pub from: i64,
+    pub to: i64,
+    #[serde(default)]
+    pub granularity: DiffGranularity,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct TagPath {
     #[validate(length(min = 1, max = 50), regex(path = *TAG_RE))]
@@ -82,19 +148,29 @@ pub struct PostPath {
     pub slug: String,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct ImportPostsQuery {
+    /// Validate the archive and report what would be created without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ExportPostsQuery {
+    /// Author to export; defaults to the caller. Exporting another author requires admin.
+    #[validate(length(min = 1, max = 50), regex(path = *USERNAME_RE))]
+    pub username: Option<String>,
+}
+
 pub fn post_pagination_params(
     query: &PostPaginationQuery,
 ) -> (
-    i64,
-    i64,
     Option<&str>,
     Option<&str>,
     Option<services::post::SortDirection>,
 ) {
-    let offset = query.offset.unwrap_or(0);
-    let limit = query.limit.unwrap_or(10);
     let search = query.search.as_deref();
     let order_by = query.order_by.as_deref();
     let order_direction = query.order_direction.map(Into::into);
-    (offset, limit, search, order_by, order_direction)
+    (search, order_by, order_direction)
 }
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, Validate)]
+pub struct SeriesIdPath {
+    pub id: Uuid,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SeriesPostPath {
+    pub id: Uuid,
+    pub post_id: Uuid,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CreateSeriesRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateSeriesRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct AddSeriesPostRequest {
+    pub post_id: Uuid,
+    /// 1-based; appended when omitted.
+    #[validate(range(min = 1))]
+    pub position: Option<i32>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ReorderSeriesRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub post_ids: Vec<Uuid>,
+}
@@ -0,0 +1,28 @@
+use crate::services;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SitemapKind {
+    Posts,
+    Tags,
+    Users,
+}
+
+impl From<SitemapKind> for services::sitemap::SitemapKind {
+    fn from(value: SitemapKind) -> Self {
+        match value {
+            SitemapKind::Posts => Self::Posts,
+            SitemapKind::Tags => Self::Tags,
+            SitemapKind::Users => Self::Users,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SitemapPath {
+    pub kind: SitemapKind,
+    #[validate(range(min = 1, max = 10_000))]
+    pub page: i64,
+}
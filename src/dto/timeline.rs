@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Deserialize, Validate)]
+pub struct TimelineQuery {
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<i64>,
+}
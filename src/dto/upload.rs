@@ -0,0 +1,15 @@
+use crate::services::upload::UploadPurpose;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, Validate)]
+pub struct UploadQuery {
+    /// `avatar` or `post_cover`.
+    pub purpose: UploadPurpose,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UploadPath {
+    pub id: Uuid,
+}
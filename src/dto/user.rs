@@ -1,3 +1,4 @@
+use crate::dto::validation::USERNAME_RE;
 use serde::Deserialize;
 use uuid::Uuid;
 use validator::Validate;
@@ -11,3 +12,9 @@ pub struct UserIdPath {
 pub struct FollowRequest {
     pub user_id: Uuid,
 }
+
+#[derive(Deserialize, Validate)]
+pub struct ChangeUsernameRequest {
+    #[validate(length(min = 3, max = 30), regex(path = *USERNAME_RE))]
+    pub username: String,
+}
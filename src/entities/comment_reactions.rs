@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "comment_reactions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub comment_id: Uuid,
+    pub user_id: Uuid,
+    /// One of `services::comment_reaction::Reaction`; `(comment_id, user_id, reaction)` is unique.
+    pub reaction: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::post_comments::Entity",
+        from = "Column::CommentId",
+        to = "super::post_comments::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    PostComments,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Users,
+}
+
+impl Related<super::post_comments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostComments.def()
+    }
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
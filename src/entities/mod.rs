@@ -1,15 +1,27 @@
 pub mod bookmark_folders;
+pub mod comment_moderation_log;
+pub mod comment_reactions;
+pub mod holding_audits;
 pub mod holding_types;
 pub mod holdings;
 pub mod notifications;
 pub mod post_bookmarks;
 pub mod post_comments;
 pub mod post_likes;
+pub mod post_revisions;
+pub mod post_share_tokens;
+pub mod post_slug_history;
+pub mod post_trending_scores;
 pub mod post_views;
 pub mod posts;
 pub mod posts_to_tags;
 pub mod profiles;
+pub mod series;
+pub mod series_posts;
 pub mod sessions;
+pub mod tag_follows;
 pub mod tags;
+pub mod uploads;
 pub mod user_follows;
+pub mod username_history;
 pub mod users;
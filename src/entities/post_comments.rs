@@ -12,10 +12,18 @@ pub struct Model {
     pub post_id: Uuid,
     pub parent_comment_id: Option<Uuid>,
     pub created_by: Uuid,
+    /// Bumped on every edit; exposed as the `ETag` for `If-Match` checks.
+    pub version: i32,
+    /// `approved`, `pending` or `hidden`; see `services::comment_moderation`.
+    pub status: String,
+    /// Reactions of every kind, kept in step by `services::counter::adjust_comment_reactions`.
+    pub reaction_count: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::comment_reactions::Entity")]
+    CommentReactions,
     #[sea_orm(
         belongs_to = "super::posts::Entity",
         from = "Column::PostId",
@@ -34,6 +42,12 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::comment_reactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommentReactions.def()
+    }
+}
+
 impl Related<super::posts::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Posts.def()
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "post_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub post_id: Uuid,
+    pub revision_number: i32,
+    pub title: String,
+    pub body: Option<String>,
+    pub slug: String,
+    pub created_by: Uuid,
+    pub created_at: DateTimeWithTimeZone,
+    pub rendered_html: Option<String>,
+    pub rendered_toc: Option<String>,
+    pub word_count: Option<i32>,
+    pub render_version: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::posts::Entity",
+        from = "Column::PostId",
+        to = "super::posts::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Posts,
+}
+
+impl Related<super::posts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Posts.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,12 @@ pub struct Model {
     pub user_id: Option<Uuid>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    /// SHA-256 of IP address and user agent, used to deduplicate anonymous views.
+    pub fingerprint: Option<String>,
+    /// Request came from a known crawler or carried no user agent.
+    pub is_bot: bool,
+    /// Repeat of a view already counted within the dedup window.
+    pub is_duplicate: bool,
     pub created_at: Option<DateTimeWithTimeZone>,
     pub updated_at: Option<DateTimeWithTimeZone>,
     pub deleted_at: Option<DateTimeWithTimeZone>,
@@ -15,13 +15,24 @@ pub struct Model {
     pub photo_url: Option<String>,
     pub published: Option<bool>,
     pub published_at: Option<DateTimeWithTimeZone>,
+    pub published_notified_at: Option<DateTimeWithTimeZone>,
     pub view_count: Option<i64>,
     pub like_count: Option<i64>,
     pub bookmark_count: Option<i64>,
+    /// `public`, `unlisted`, `followers` or `private`; see `services::visibility`.
+    pub visibility: String,
+    /// Bumped on every edit; exposed as the `ETag` for `If-Match` checks.
+    pub version: i32,
+    /// New comments are rejected while set.
+    pub comments_locked: bool,
+    /// Comments from people who have no approved comment on the author's posts wait for approval.
+    pub comment_approval_required: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::comment_moderation_log::Entity")]
+    CommentModerationLog,
     #[sea_orm(has_many = "super::post_bookmarks::Entity")]
     PostBookmarks,
     #[sea_orm(has_many = "super::post_comments::Entity")]
@@ -40,6 +51,16 @@ pub enum Relation {
     PostLikes,
     #[sea_orm(has_many = "super::post_views::Entity")]
     PostViews,
+    #[sea_orm(has_many = "super::post_revisions::Entity")]
+    PostRevisions,
+    #[sea_orm(has_many = "super::post_slug_history::Entity")]
+    PostSlugHistory,
+}
+
+impl Related<super::comment_moderation_log::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CommentModerationLog.def()
+    }
 }
 
 impl Related<super::post_bookmarks::Entity> for Entity {
@@ -54,6 +75,18 @@ impl Related<super::post_comments::Entity> for Entity {
     }
 }
 
+impl Related<super::post_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostRevisions.def()
+    }
+}
+
+impl Related<super::post_slug_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostSlugHistory.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
@@ -13,6 +13,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::posts_to_tags::Entity")]
     PostsToTags,
+    #[sea_orm(has_many = "super::tag_follows::Entity")]
+    TagFollows,
 }
 
 impl Related<super::posts::Entity> for Entity {
@@ -25,4 +27,10 @@ impl Related<super::posts::Entity> for Entity {
     }
 }
 
+impl Related<super::tag_follows::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TagFollows.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -23,6 +23,8 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::series::Entity")]
+    Series,
     #[sea_orm(has_many = "super::bookmark_folders::Entity")]
     BookmarkFolders,
     #[sea_orm(has_many = "super::post_bookmarks::Entity")]
@@ -39,6 +41,8 @@ pub enum Relation {
     Profile,
     #[sea_orm(has_many = "super::sessions::Entity")]
     Sessions,
+    #[sea_orm(has_many = "super::username_history::Entity")]
+    UsernameHistory,
 }
 
 impl Related<super::bookmark_folders::Entity> for Entity {
@@ -89,4 +93,16 @@ impl Related<super::sessions::Entity> for Entity {
     }
 }
 
+impl Related<super::series::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Series.def()
+    }
+}
+
+impl Related<super::username_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UsernameHistory.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,7 +1,7 @@
 use crate::response::ApiResponse;
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use sea_orm::DbErr;
@@ -15,6 +15,13 @@ pub enum AppError {
     Unauthorized(String),
     Forbidden(String),
     InternalServerError(String),
+    /// A write was sent without the `If-Match` header it requires.
+    PreconditionRequired(String),
+    /// `If-Match` named an outdated version; the body and `ETag` carry the current one.
+    PreconditionFailed {
+        message: String,
+        current_version: i32,
+    },
 }
 
 impl IntoResponse for AppError {
@@ -33,6 +40,25 @@ impl IntoResponse for AppError {
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::PreconditionRequired(msg) => (StatusCode::PRECONDITION_REQUIRED, msg),
+            AppError::PreconditionFailed {
+                message,
+                current_version,
+            } => {
+                let body = Json(ApiResponse {
+                    success: false,
+                    message: message.clone(),
+                    data: Some(serde_json::json!({ "current_version": current_version })),
+                    error: Some(message),
+                    meta: None,
+                });
+                return (
+                    StatusCode::PRECONDITION_FAILED,
+                    [(header::ETAG, crate::precondition::etag(current_version))],
+                    body,
+                )
+                    .into_response();
+            }
         };
 
         let body = Json(ApiResponse::<serde_json::Value> {
@@ -6,6 +6,7 @@ use crate::dto::bookmark::{
 };
 use crate::error::AppError;
 use crate::models::bookmark::{BookmarkFolderResponse, BookmarkResponse, ToggleBookmarkResponse};
+use crate::pagination::Page;
 use crate::response::ApiResponse;
 use crate::services::{self, bookmark::BookmarkError};
 use axum::{
@@ -65,25 +66,21 @@ pub async fn get_bookmarks(
     auth_user: AuthUser,
     Valid(query): Valid<Query<BookmarkQuery>>,
 ) -> Result<Json<ApiResponse<Vec<BookmarkResponse>>>, AppError> {
-    let limit = query.limit.unwrap_or(50);
-    let offset = query.offset.unwrap_or(0);
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(50),
+        query.cursor.as_deref(),
+    )?;
     let folder_filter = parse_folder_filter(query.folder_id.clone())?;
-    let (bookmarks, total) = services::bookmark::get_bookmarks_by_user(
-        &pool,
-        auth_user.id,
-        folder_filter,
-        limit,
-        offset,
-    )
-    .await
-    .map_err(map_bookmark_error)?;
+    let bookmarks =
+        services::bookmark::get_bookmarks_by_user(&pool, auth_user.id, folder_filter, &page)
+            .await
+            .map_err(map_bookmark_error)?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Bookmarks fetched successfully",
         bookmarks,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
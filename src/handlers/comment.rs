@@ -1,16 +1,25 @@
 use crate::auth::AuthUser;
 use crate::database::DbPool;
-use crate::dto::comment::{CommentPath, CommentRequest};
-use crate::dto::common::PostIdPath;
+use crate::dto::comment::{
+    CommentListQuery, CommentPath, CommentReactionPath, CommentRequest, CreateCommentRequest,
+};
+use crate::dto::common::{PostIdPath, ShareTokenQuery};
 use crate::error::AppError;
-use crate::models::comment::CommentResponse;
+use crate::models::comment::{CommentReactions, CommentResponse};
+use crate::pagination::Page;
+use crate::precondition::{self, IfMatch};
 use crate::response::ApiResponse;
-use crate::services::{self, comment::CommentError};
+use crate::services::{
+    self,
+    comment::{CommentError, CommentSort},
+    comment_reaction::CommentReactionError,
+    visibility::PostAccess,
+};
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, put},
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    routing::{get, post, put},
 };
 use axum_valid::Valid;
 
@@ -18,8 +27,18 @@ fn map_comment_error(err: CommentError) -> AppError {
     match err {
         CommentError::Db(err) => AppError::from(err),
         CommentError::PostNotFound => AppError::NotFound("Post not found".to_string()),
+        CommentError::CommentsLocked => {
+            AppError::Forbidden("Comments on this post are locked".to_string())
+        }
         CommentError::CommentNotFound => AppError::NotFound("Comment not found".to_string()),
+        CommentError::ParentNotFound => {
+            AppError::BadRequest("Parent comment not found on this post".to_string())
+        }
         CommentError::NotOwner => AppError::Forbidden("You are not the comment author".to_string()),
+        CommentError::VersionMismatch { current } => AppError::PreconditionFailed {
+            message: "Comment was modified since it was loaded; reload it and retry".to_string(),
+            current_version: current,
+        },
     }
 }
 
@@ -27,11 +46,19 @@ pub async fn create_comment(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
-    Valid(Json(req)): Valid<Json<CommentRequest>>,
+    Valid(query): Valid<Query<ShareTokenQuery>>,
+    Valid(Json(req)): Valid<Json<CreateCommentRequest>>,
 ) -> Result<(StatusCode, Json<ApiResponse<CommentResponse>>), AppError> {
-    let comment = services::comment::create_comment(&pool, params.id, req.text, auth_user.id)
-        .await
-        .map_err(map_comment_error)?;
+    let comment = services::comment::create_comment(
+        &pool,
+        params.id,
+        req.text,
+        req.parent_comment_id,
+        auth_user.id,
+        query.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_comment_error)?;
 
     Ok((
         StatusCode::CREATED,
@@ -42,40 +69,85 @@ pub async fn create_comment(
     ))
 }
 
+fn map_comment_reaction_error(err: CommentReactionError) -> AppError {
+    match err {
+        CommentReactionError::Db(err) => AppError::from(err),
+        CommentReactionError::CommentNotFound => {
+            AppError::NotFound("Comment not found".to_string())
+        }
+    }
+}
+
 pub async fn get_comments_by_post_id(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(query): Valid<Query<CommentListQuery>>,
 ) -> Result<Json<ApiResponse<Vec<CommentResponse>>>, AppError> {
-    let comments = services::comment::get_comments_by_post_id(&pool, params.id)
-        .await
-        .map_err(map_comment_error)?;
+    if query.cursor.is_some() && query.sort != CommentSort::Newest {
+        return Err(AppError::BadRequest(
+            "cursor can only be combined with sort=newest".to_string(),
+        ));
+    }
+    let paginated = query.offset.is_some() || query.limit.is_some() || query.cursor.is_some();
+    let page = paginated
+        .then(|| {
+            Page::from_query(
+                query.offset,
+                query.limit.unwrap_or(20),
+                query.cursor.as_deref(),
+            )
+        })
+        .transpose()?;
 
-    Ok(Json(ApiResponse::success_with_message(
-        "Comments fetched successfully",
-        comments,
-    )))
+    let comments = services::comment::get_comments_by_post_id(
+        &pool,
+        params.id,
+        page.as_ref(),
+        query.sort,
+        &PostAccess::new(auth_user.map(|user| user.id), query.share_token.as_deref()),
+    )
+    .await
+    .map_err(map_comment_error)?;
+
+    let message = "Comments fetched successfully";
+    Ok(Json(match page {
+        Some(page) => ApiResponse::with_page_message(message, comments, &page),
+        None => ApiResponse::success_with_message(message, comments.items),
+    }))
 }
 
 pub async fn update_comment(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
+    if_match: IfMatch,
     Valid(Path(params)): Valid<Path<CommentPath>>,
     Valid(Json(req)): Valid<Json<CommentRequest>>,
-) -> Result<Json<ApiResponse<CommentResponse>>, AppError> {
+) -> Result<
+    (
+        [(header::HeaderName, String); 1],
+        Json<ApiResponse<CommentResponse>>,
+    ),
+    AppError,
+> {
     let comment = services::comment::update_comment(
         &pool,
         params.id,
         params.comment_id,
         req.text,
         auth_user.id,
+        if_match.expected(),
     )
     .await
     .map_err(map_comment_error)?;
 
-    Ok(Json(ApiResponse::success_with_message(
-        "Comment updated successfully",
-        comment,
-    )))
+    Ok((
+        [(header::ETAG, precondition::etag(comment.version))],
+        Json(ApiResponse::success_with_message(
+            "Comment updated successfully",
+            comment,
+        )),
+    ))
 }
 
 pub async fn delete_comment(
@@ -93,6 +165,50 @@ pub async fn delete_comment(
     )))
 }
 
+pub async fn add_reaction(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<CommentReactionPath>>,
+    Valid(query): Valid<Query<ShareTokenQuery>>,
+) -> Result<Json<ApiResponse<CommentReactions>>, AppError> {
+    let reactions = services::comment_reaction::add_reaction(
+        &pool,
+        params.id,
+        params.comment_id,
+        auth_user.id,
+        params.reaction,
+        query.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_comment_reaction_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Reaction added successfully",
+        reactions,
+    )))
+}
+
+pub async fn remove_reaction(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<CommentReactionPath>>,
+    Valid(query): Valid<Query<ShareTokenQuery>>,
+) -> Result<Json<ApiResponse<CommentReactions>>, AppError> {
+    let reactions = services::comment_reaction::remove_reaction(
+        &pool,
+        params.id,
+        params.comment_id,
+        auth_user.id,
+        params.reaction,
+        query.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_comment_reaction_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Reaction removed successfully",
+        reactions,
+    )))
+}
+
 pub fn routes() -> Router<DbPool> {
     Router::new()
         .route(
@@ -103,4 +219,8 @@ pub fn routes() -> Router<DbPool> {
             "/api/posts/{id}/comments/{comment_id}",
             put(update_comment).delete(delete_comment),
         )
+        .route(
+            "/api/posts/{id}/comments/{comment_id}/reactions/{reaction}",
+            post(add_reaction).delete(remove_reaction),
+        )
 }
@@ -0,0 +1,162 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::comment::{
+    CommentPath, CommentSettingsRequest, ModerateCommentRequest, ModerationQueueQuery,
+};
+use crate::dto::common::{PaginationQuery, PostIdPath};
+use crate::error::AppError;
+use crate::models::comment::CommentResponse;
+use crate::models::comment_moderation::{CommentSettingsResponse, ModerationLogEntry};
+use crate::pagination::Page;
+use crate::response::ApiResponse;
+use crate::services::{
+    self,
+    comment_moderation::{ModerationError, Moderator},
+};
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    routing::{get, post},
+};
+use axum_valid::Valid;
+
+fn map_moderation_error(err: ModerationError) -> AppError {
+    match err {
+        ModerationError::Db(err) => AppError::from(err),
+        ModerationError::PostNotFound => AppError::NotFound("Post not found".to_string()),
+        ModerationError::CommentNotFound => AppError::NotFound("Comment not found".to_string()),
+        ModerationError::NotModerator => AppError::Forbidden(
+            "Only the post author or an admin can moderate its comments".to_string(),
+        ),
+    }
+}
+
+fn moderator(auth_user: &AuthUser) -> Moderator {
+    Moderator {
+        id: auth_user.id,
+        is_admin: auth_user.is_super_admin,
+    }
+}
+
+/// Approves, hides or removes a comment on a post the caller moderates.
+pub async fn moderate_comment(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<CommentPath>>,
+    Valid(Json(req)): Valid<Json<ModerateCommentRequest>>,
+) -> Result<Json<ApiResponse<CommentResponse>>, AppError> {
+    let comment = services::comment_moderation::moderate_comment(
+        &pool,
+        params.id,
+        params.comment_id,
+        moderator(&auth_user),
+        req.action,
+        req.reason,
+    )
+    .await
+    .map_err(map_moderation_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Comment moderated successfully",
+        comment,
+    )))
+}
+
+pub async fn get_comment_settings(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+) -> Result<Json<ApiResponse<CommentSettingsResponse>>, AppError> {
+    let settings =
+        services::comment_moderation::get_settings(&pool, params.id, moderator(&auth_user))
+            .await
+            .map_err(map_moderation_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Comment settings fetched successfully",
+        settings,
+    )))
+}
+
+pub async fn update_comment_settings(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(Json(req)): Valid<Json<CommentSettingsRequest>>,
+) -> Result<Json<ApiResponse<CommentSettingsResponse>>, AppError> {
+    let settings = services::comment_moderation::update_settings(
+        &pool,
+        params.id,
+        moderator(&auth_user),
+        req.comments_locked,
+        req.comment_approval_required,
+    )
+    .await
+    .map_err(map_moderation_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Comment settings updated successfully",
+        settings,
+    )))
+}
+
+pub async fn get_moderation_queue(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<ModerationQueueQuery>>,
+) -> Result<Json<ApiResponse<Vec<CommentResponse>>>, AppError> {
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(20),
+        query.cursor.as_deref(),
+    )?;
+    let comments = services::comment_moderation::moderation_queue(
+        &pool,
+        moderator(&auth_user),
+        query.post_id,
+        &page,
+    )
+    .await
+    .map_err(map_moderation_error)?;
+    Ok(Json(ApiResponse::with_page_message(
+        "Moderation queue fetched successfully",
+        comments,
+        &page,
+    )))
+}
+
+pub async fn get_moderation_log(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(Query(query)): Valid<Query<PaginationQuery>>,
+) -> Result<Json<ApiResponse<Vec<ModerationLogEntry>>>, AppError> {
+    let page = Page::from_query(query.offset, query.limit.unwrap_or(20), None)?;
+    let entries = services::comment_moderation::moderation_log(
+        &pool,
+        params.id,
+        moderator(&auth_user),
+        &page,
+    )
+    .await
+    .map_err(map_moderation_error)?;
+    Ok(Json(ApiResponse::with_page_message(
+        "Moderation log fetched successfully",
+        entries,
+        &page,
+    )))
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route("/api/comments/moderation-queue", get(get_moderation_queue))
+        .route(
+            "/api/posts/{id}/comment-settings",
+            get(get_comment_settings).put(update_comment_settings),
+        )
+        .route(
+            "/api/posts/{id}/comments/moderation-log",
+            get(get_moderation_log),
+        )
+        .route(
+            "/api/posts/{id}/comments/{comment_id}/moderate",
+            post(moderate_comment),
+        )
+}
@@ -0,0 +1,153 @@
+use crate::config::SiteConfig;
+use crate::database::DbPool;
+use crate::dto::feed::{AuthorFeedPath, SiteFeedPath, TagFeedPath};
+use crate::error::AppError;
+use crate::services::{
+    self,
+    feed::{FeedChannel, FeedFormat},
+    post::FeedScope,
+};
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use axum_valid::Valid;
+use chrono::{DateTime, Utc};
+
+const FEED_ITEM_LIMIT: i64 = 50;
+
+fn http_date(value: DateTime<Utc>) -> String {
+    value.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// `If-None-Match` wins over `If-Modified-Since` when both are sent (RFC 9110 13.2.2).
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: DateTime<Utc>) -> bool {
+    if let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    {
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag);
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| last_modified <= since.with_timezone(&Utc))
+}
+
+async fn feed_response(
+    pool: &DbPool,
+    headers: &HeaderMap,
+    format: FeedFormat,
+    scope: FeedScope<'_>,
+    channel: FeedChannel,
+) -> Result<Response, AppError> {
+    let Some(posts) = services::post::get_feed_posts(pool, scope, FEED_ITEM_LIMIT).await? else {
+        return Err(AppError::NotFound("Feed not found".to_string()));
+    };
+
+    let etag = services::feed::etag(format, &channel, &posts);
+    let last_modified = services::feed::last_modified(&posts);
+    let mut response_headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&http_date(last_modified)) {
+        response_headers.insert(header::LAST_MODIFIED, value);
+    }
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=300"),
+    );
+
+    if is_not_modified(headers, &etag, last_modified) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    let body = services::feed::render(format, SiteConfig::get(), &channel, &posts);
+    Ok((StatusCode::OK, response_headers, body).into_response())
+}
+
+pub async fn site_feed(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Valid(Path(params)): Valid<Path<SiteFeedPath>>,
+) -> Result<Response, AppError> {
+    let format: FeedFormat = params.format.into();
+    let site = SiteConfig::get();
+    let channel = FeedChannel {
+        title: site.title.clone(),
+        description: site.description.clone(),
+        home_path: "/".to_string(),
+        feed_path: format!("/api/feeds/{}", format.extension()),
+    };
+    feed_response(&pool, &headers, format, FeedScope::Site, channel).await
+}
+
+pub async fn author_feed(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Valid(Path(params)): Valid<Path<AuthorFeedPath>>,
+) -> Result<Response, AppError> {
+    let format: FeedFormat = params.format.into();
+    let site = SiteConfig::get();
+    let channel = FeedChannel {
+        title: format!("{} - {}", params.username, site.title),
+        description: format!("Latest posts by {}", params.username),
+        home_path: format!("/{}", params.username),
+        feed_path: format!(
+            "/api/feeds/users/{}/{}",
+            params.username,
+            format.extension()
+        ),
+    };
+    feed_response(
+        &pool,
+        &headers,
+        format,
+        FeedScope::Author(&params.username),
+        channel,
+    )
+    .await
+}
+
+pub async fn tag_feed(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Valid(Path(params)): Valid<Path<TagFeedPath>>,
+) -> Result<Response, AppError> {
+    let format: FeedFormat = params.format.into();
+    let site = SiteConfig::get();
+    let channel = FeedChannel {
+        title: format!("#{} - {}", params.tag, site.title),
+        description: format!("Latest posts tagged {}", params.tag),
+        home_path: format!("/tags/{}", params.tag),
+        feed_path: format!("/api/feeds/tags/{}/{}", params.tag, format.extension()),
+    };
+    feed_response(
+        &pool,
+        &headers,
+        format,
+        FeedScope::Tag(&params.tag),
+        channel,
+    )
+    .await
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route("/api/feeds/{format}", get(site_feed))
+        .route("/api/feeds/users/{username}/{format}", get(author_feed))
+        .route("/api/feeds/tags/{tag}/{format}", get(tag_feed))
+}
@@ -1,12 +1,14 @@
 use crate::auth::AuthUser;
 use crate::database::DbPool;
 use crate::dto::holding::{
-    CompareQuery, CreateHoldingRequest, DuplicateHoldingRequest, HoldingPath, HoldingQuery,
-    MonthlyQuery, SummaryQuery, TrendsQuery, UpdateHoldingRequest,
+    CompareQuery, CreateHoldingRequest, DuplicateHoldingRequest, HoldingHistoryPath, HoldingPath,
+    HoldingQuery, MonthlyQuery, PositionHistoryQuery, ProjectionMode, ProjectionRequest,
+    SummaryQuery, TrendsQuery, UpdateHoldingRequest,
 };
 use crate::error::AppError;
 use crate::models::holding::{
-    DuplicateResultItem, HoldingMonthComparisonResponse, HoldingMonthlyDataResponse,
+    DuplicateResultItem, HoldingAuditResponse, HoldingMonthComparisonResponse,
+    HoldingMonthlyDataResponse, HoldingPositionHistoryResponse, HoldingProjectionResponse,
     HoldingResponse, HoldingSummaryResponse, HoldingSyncResponse, HoldingTrendResponse,
     HoldingTypeResponse,
 };
@@ -32,6 +34,13 @@ fn map_holding_error(err: HoldingError) -> AppError {
         HoldingError::DuplicateSameMonth => {
             AppError::BadRequest("Cannot duplicate holdings into the same month".to_string())
         }
+        HoldingError::InsufficientHistory => AppError::BadRequest(
+            "At least three consecutive months of holdings are needed for a Monte Carlo projection"
+                .to_string(),
+        ),
+        HoldingError::HistoryEntryNotFound => {
+            AppError::NotFound("History entry not found".to_string())
+        }
     }
 }
 
@@ -79,6 +88,35 @@ pub async fn get_holding_by_id(
     )))
 }
 
+pub async fn get_holding_history(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<HoldingPath>>,
+) -> Result<Json<ApiResponse<Vec<HoldingAuditResponse>>>, AppError> {
+    let history = services::holding::get_holding_history(&pool, params.id, auth_user.id)
+        .await
+        .map_err(map_holding_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Holding history fetched successfully",
+        history,
+    )))
+}
+
+pub async fn restore_holding_version(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<HoldingHistoryPath>>,
+) -> Result<Json<ApiResponse<HoldingResponse>>, AppError> {
+    let holding =
+        services::holding::restore_holding_version(&pool, params.id, params.audit_id, auth_user.id)
+            .await
+            .map_err(map_holding_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Holding restored successfully",
+        holding,
+    )))
+}
+
 pub async fn create_holding(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
@@ -264,6 +302,66 @@ pub async fn get_monthly_data(
     )))
 }
 
+pub async fn get_position_history(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<PositionHistoryQuery>>,
+) -> Result<Json<ApiResponse<HoldingPositionHistoryResponse>>, AppError> {
+    let key = match (query.symbol, query.name, query.platform) {
+        (Some(symbol), _, _) => services::holding::PositionKey::Symbol(symbol),
+        (None, Some(name), Some(platform)) => {
+            services::holding::PositionKey::NamePlatform { name, platform }
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Either symbol or name and platform are required".to_string(),
+            ));
+        }
+    };
+
+    let result = services::holding::position_history(&pool, auth_user.id, key)
+        .await
+        .map_err(map_holding_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Position history fetched successfully",
+        result,
+    )))
+}
+
+pub async fn get_projection(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Json(req)): Valid<Json<ProjectionRequest>>,
+) -> Result<Json<ApiResponse<HoldingProjectionResponse>>, AppError> {
+    let (current_month, current_year) = services::holding::default_current_month_year();
+    let monte_carlo =
+        (req.mode == ProjectionMode::MonteCarlo).then(|| services::holding::MonteCarloInput {
+            simulations: req.simulations.unwrap_or(1000),
+            seed: req.seed,
+        });
+
+    let result = services::holding::projection(
+        &pool,
+        auth_user.id,
+        services::holding::ProjectionInput {
+            month: req.month.unwrap_or(current_month),
+            year: req.year.unwrap_or(current_year),
+            years: req.years,
+            monthly_contribution: req.monthly_contribution,
+            expected_returns: req.expected_returns,
+            default_return: req.default_return.unwrap_or(5.0),
+            inflation: req.inflation,
+            monte_carlo,
+        },
+    )
+    .await
+    .map_err(map_holding_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Holdings projection calculated successfully",
+        result,
+    )))
+}
+
 pub async fn sync_prices(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
@@ -314,6 +412,8 @@ pub fn routes() -> Router<DbPool> {
         .route("/api/holdings/trends", get(get_trends))
         .route("/api/holdings/compare", get(compare_months))
         .route("/api/holdings/monthly", get(get_monthly_data))
+        .route("/api/holdings/positions/history", get(get_position_history))
+        .route("/api/holdings/projection", post(get_projection))
         .route("/api/holdings/duplicate", post(duplicate_holdings))
         .route("/api/holdings/sync", post(sync_prices))
         .route(
@@ -322,5 +422,10 @@ pub fn routes() -> Router<DbPool> {
                 .put(update_holding)
                 .delete(delete_holding),
         )
+        .route("/api/holdings/{id}/history", get(get_holding_history))
+        .route(
+            "/api/holdings/{id}/history/{audit_id}/restore",
+            post(restore_holding_version),
+        )
         .route("/api/holding-types", get(get_holding_types))
 }
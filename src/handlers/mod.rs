@@ -1,12 +1,20 @@
 mod auth;
 mod bookmark;
 mod comment;
+mod comment_moderation;
+mod feed;
 mod health;
 mod holding;
 mod notification;
 mod post;
+mod post_archive;
+mod post_trash;
 mod report;
+mod series;
+mod sitemap;
 mod tag;
+mod timeline;
+mod upload;
 mod user;
 
 use crate::database::DbPool;
@@ -20,11 +28,19 @@ pub fn create_router() -> Router<DbPool> {
         .merge(auth::routes())
         .merge(bookmark::routes())
         .merge(comment::routes())
+        .merge(comment_moderation::routes())
+        .merge(feed::routes())
         .merge(holding::routes())
         .merge(notification::routes())
         .merge(post::routes())
+        .merge(post_archive::routes())
+        .merge(post_trash::routes())
         .merge(report::routes())
+        .merge(series::routes())
+        .merge(sitemap::routes())
         .merge(tag::routes())
+        .merge(timeline::routes())
+        .merge(upload::routes())
         .merge(user::routes())
         // TraceLayer should be added early to trace all requests
         // It provides good defaults: logs method, uri, status, latency automatically
@@ -3,6 +3,7 @@ use crate::database::DbPool;
 use crate::dto::notification::{NotificationPath, NotificationQuery};
 use crate::error::AppError;
 use crate::models::notification::{MarkAllReadResponse, NotificationResponse, UnreadCountResponse};
+use crate::pagination::Page;
 use crate::response::ApiResponse;
 use crate::services;
 use axum::{
@@ -17,18 +18,18 @@ pub async fn get_notifications(
     auth_user: AuthUser,
     Valid(query): Valid<Query<NotificationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<NotificationResponse>>>, AppError> {
-    let limit = query.limit.unwrap_or(10);
-    let offset = query.offset.unwrap_or(0);
-    let (notifications, total) =
-        services::notification::get_notifications(&pool, auth_user.id, query.unread, limit, offset)
-            .await?;
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let notifications =
+        services::notification::get_notifications(&pool, auth_user.id, query.unread, &page).await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved notifications",
         notifications,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
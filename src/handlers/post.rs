@@ -1,20 +1,34 @@
 use crate::auth::AuthUser;
+use crate::config::ViewConfig;
 use crate::database::DbPool;
-use crate::dto::common::{PostIdPath, UsernamePath};
+use crate::dto::common::{PaginationQuery, PostIdPath, ShareTokenQuery, UsernamePath};
 use crate::dto::post::{
-    CreatePostRequest, PostPaginationQuery, PostPath, RandomPostQuery, TagPath, UpdatePostRequest,
-    post_pagination_params,
+    CreatePostRequest, PostDetailQuery, PostPaginationQuery, PostPath, PostRevisionPath,
+    RandomPostQuery, RevisionDiffQuery, ShareTokenPath, TagPath, TrendingPostQuery,
+    UpdatePostRequest, post_pagination_params,
 };
 use crate::error::AppError;
 use crate::models::post::{Post, SitemapPost};
+use crate::models::post_revision::{
+    PostRevisionDiffResponse, PostRevisionResponse, PostRevisionSummary,
+};
+use crate::models::post_share::ShareTokenResponse;
+use crate::pagination::Page;
+use crate::precondition::{self, IfMatch};
 use crate::response::ApiResponse;
-use crate::services;
+use crate::services::{
+    self, post::UpdatePostError, post_share::PostShareError, trending::TrendingWindow,
+    visibility::PostAccess,
+};
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
-    routing::{get, post},
+    extract::{ConnectInfo, Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
 };
 use axum_valid::Valid;
+use std::net::SocketAddr;
 use uuid::Uuid;
 
 pub async fn create_post(
@@ -30,7 +44,10 @@ pub async fn create_post(
             slug: req.slug,
             body: req.body,
             published: req.published,
+            published_at: req.published_at,
+            visibility: req.visibility,
             tags: req.tags,
+            notify_followers: true,
         },
         auth_user.id,
     )
@@ -66,6 +83,18 @@ fn map_post_like_error(err: services::post_like::PostLikeError) -> AppError {
     }
 }
 
+fn map_post_revision_error(err: services::post_revision::PostRevisionError) -> AppError {
+    match err {
+        services::post_revision::PostRevisionError::Db(err) => AppError::from(err),
+        services::post_revision::PostRevisionError::PostNotFound => {
+            AppError::NotFound("Post not found".to_string())
+        }
+        services::post_revision::PostRevisionError::RevisionNotFound => {
+            AppError::NotFound("Revision not found".to_string())
+        }
+    }
+}
+
 async fn ensure_author(pool: &DbPool, post_id: Uuid, auth_user: &AuthUser) -> Result<(), AppError> {
     match services::post::is_author(pool, post_id, auth_user.id).await? {
         Some(true) => Ok(()),
@@ -76,23 +105,38 @@ async fn ensure_author(pool: &DbPool, post_id: Uuid, auth_user: &AuthUser) -> Re
     }
 }
 
+/// Cursors follow the default newest-first order, so they cannot be combined with `search`
+/// or a custom ordering.
+fn post_page(query: &PostPaginationQuery) -> Result<Page, AppError> {
+    let (search, order_by, order_direction) = post_pagination_params(query);
+    if query.cursor.is_some() && !services::post::is_keyset_order(search, order_by, order_direction)
+    {
+        return Err(AppError::BadRequest(
+            "cursor cannot be combined with search or a custom ordering".to_string(),
+        ));
+    }
+    Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )
+}
+
 pub async fn get_posts(
     State(pool): State<DbPool>,
     Valid(query): Valid<Query<PostPaginationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
     let client = pool;
-    let (offset, limit, search, order_by, order_direction) = post_pagination_params(&query);
+    let page = post_page(&query)?;
+    let (search, order_by, order_direction) = post_pagination_params(&query);
 
-    let (posts, total) =
-        services::post::get_all_posts(&client, offset, limit, search, order_by, order_direction)
-            .await?;
+    let posts =
+        services::post::get_all_posts(&client, &page, search, order_by, order_direction).await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved posts",
         posts,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
@@ -112,17 +156,36 @@ pub async fn get_random_posts(
 
 pub async fn get_trending_posts(
     State(pool): State<DbPool>,
-    Valid(query): Valid<Query<RandomPostQuery>>,
+    Valid(query): Valid<Query<TrendingPostQuery>>,
 ) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
     let client = pool;
     let limit = query.limit.unwrap_or(10).min(100);
-    let posts = services::post::get_trending_posts(&client, limit).await?;
+    let window = query.window.unwrap_or(TrendingWindow::Week);
+    let posts = services::trending::get_trending_posts(&client, window, limit).await?;
     Ok(Json(ApiResponse::success_with_message(
         "Successfully retrieved trending posts",
         posts,
     )))
 }
 
+pub async fn get_related_posts(
+    State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(query): Valid<Query<RandomPostQuery>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
+) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
+    let limit = query.limit.unwrap_or(5).min(20);
+    let access = PostAccess::new(auth_user.map(|user| user.id), share.share_token.as_deref());
+    match services::post::get_related_posts(&pool, params.id, limit, &access).await? {
+        Some(posts) => Ok(Json(ApiResponse::success_with_message(
+            "Successfully retrieved related posts",
+            posts,
+        ))),
+        None => Err(AppError::NotFound("Post not found".to_string())),
+    }
+}
+
 pub async fn get_posts_for_sitemap(
     State(pool): State<DbPool>,
 ) -> Result<Json<ApiResponse<Vec<SitemapPost>>>, AppError> {
@@ -140,25 +203,23 @@ pub async fn get_posts_by_tag(
     Valid(query): Valid<Query<PostPaginationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
     let client = pool;
-    let (offset, limit, search, order_by, order_direction) = post_pagination_params(&query);
+    let page = post_page(&query)?;
+    let (search, order_by, order_direction) = post_pagination_params(&query);
 
-    let (posts, total) = services::post::get_posts_by_tag(
+    let posts = services::post::get_posts_by_tag(
         &client,
         &tag_path.tag,
-        offset,
-        limit,
+        &page,
         search,
         order_by,
         order_direction,
     )
     .await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved posts by tag",
         posts,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
@@ -169,19 +230,32 @@ fn header_string(headers: &axum::http::HeaderMap, name: &'static str) -> Option<
         .map(ToOwned::to_owned)
 }
 
+/// Anonymous views are accepted; they are deduplicated by IP and user agent.
 pub async fn record_view(
     State(pool): State<DbPool>,
-    auth_user: AuthUser,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    auth_user: Option<AuthUser>,
     headers: axum::http::HeaderMap,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
-    let ip_address =
-        header_string(&headers, "x-forwarded-for").or_else(|| header_string(&headers, "x-real-ip"));
+    let ip_address = services::post_view::client_ip(
+        header_string(&headers, "x-forwarded-for").as_deref(),
+        peer.ip(),
+        ViewConfig::get().trusted_proxy_hops,
+    );
     let user_agent = header_string(&headers, "user-agent");
 
-    services::post_view::record_view(&pool, params.id, Some(auth_user.id), ip_address, user_agent)
-        .await
-        .map_err(map_post_view_error)?;
+    services::post_view::record_view(
+        &pool,
+        params.id,
+        auth_user.map(|user| user.id),
+        Some(ip_address),
+        user_agent,
+        share.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_post_view_error)?;
 
     Ok(Json(ApiResponse::success_with_message(
         "View recorded successfully",
@@ -191,30 +265,36 @@ pub async fn record_view(
 
 pub async fn get_post_views(
     State(pool): State<DbPool>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
     Valid(query): Valid<Query<PostPaginationQuery>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<Vec<crate::models::post_view::PostViewResponse>>>, AppError> {
-    let offset = query.offset.unwrap_or(0);
-    let limit = query.limit.unwrap_or(10);
-    let (views, total) = services::post_view::get_views_by_post_id(&pool, params.id, limit, offset)
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let access = PostAccess::new(Some(auth_user.id), share.share_token.as_deref());
+    let views = services::post_view::get_views_by_post_id(&pool, params.id, &page, &access)
         .await
         .map_err(map_post_view_error)?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved post views",
         views,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
 pub async fn get_post_view_stats(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<crate::models::post_view::PostViewStats>>, AppError> {
-    let stats = services::post_view::get_view_stats(&pool, params.id)
+    let access = PostAccess::new(auth_user.map(|user| user.id), share.share_token.as_deref());
+    let stats = services::post_view::get_view_stats(&pool, params.id, &access)
         .await
         .map_err(map_post_view_error)?;
 
@@ -228,10 +308,16 @@ pub async fn check_user_viewed(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<crate::models::post_view::ViewStatusResponse>>, AppError> {
-    let status = services::post_view::has_user_viewed_post(&pool, params.id, auth_user.id)
-        .await
-        .map_err(map_post_view_error)?;
+    let status = services::post_view::has_user_viewed_post(
+        &pool,
+        params.id,
+        auth_user.id,
+        share.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_post_view_error)?;
 
     Ok(Json(ApiResponse::success_with_message(
         "Successfully checked view status",
@@ -243,8 +329,9 @@ pub async fn like_post(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
-    services::post_like::like_post(&pool, params.id, auth_user.id)
+    services::post_like::like_post(&pool, params.id, auth_user.id, share.share_token.as_deref())
         .await
         .map_err(map_post_like_error)?;
 
@@ -258,8 +345,9 @@ pub async fn unlike_post(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
-    services::post_like::unlike_post(&pool, params.id, auth_user.id)
+    services::post_like::unlike_post(&pool, params.id, auth_user.id, share.share_token.as_deref())
         .await
         .map_err(map_post_like_error)?;
 
@@ -271,12 +359,18 @@ pub async fn unlike_post(
 
 pub async fn get_post_likes(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
     Valid(query): Valid<Query<PostPaginationQuery>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<crate::models::post_like::PostLikeListResponse>>, AppError> {
-    let offset = query.offset.unwrap_or(0);
-    let limit = query.limit.unwrap_or(10);
-    let likes = services::post_like::get_likes_by_post_id(&pool, params.id, limit, offset)
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let access = PostAccess::new(auth_user.map(|user| user.id), share.share_token.as_deref());
+    let likes = services::post_like::get_likes_by_post_id(&pool, params.id, &page, &access)
         .await
         .map_err(map_post_like_error)?;
 
@@ -288,9 +382,12 @@ pub async fn get_post_likes(
 
 pub async fn get_post_like_stats(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<crate::models::post_like::PostLikeStats>>, AppError> {
-    let stats = services::post_like::get_like_stats(&pool, params.id)
+    let access = PostAccess::new(auth_user.map(|user| user.id), share.share_token.as_deref());
+    let stats = services::post_like::get_like_stats(&pool, params.id, &access)
         .await
         .map_err(map_post_like_error)?;
 
@@ -304,10 +401,16 @@ pub async fn check_user_liked(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(share): Valid<Query<ShareTokenQuery>>,
 ) -> Result<Json<ApiResponse<crate::models::post_like::LikeStatusResponse>>, AppError> {
-    let status = services::post_like::has_user_liked_post(&pool, params.id, auth_user.id)
-        .await
-        .map_err(map_post_like_error)?;
+    let status = services::post_like::has_user_liked_post(
+        &pool,
+        params.id,
+        auth_user.id,
+        share.share_token.as_deref(),
+    )
+    .await
+    .map_err(map_post_like_error)?;
 
     Ok(Json(ApiResponse::success_with_message(
         "Like status retrieved successfully",
@@ -317,48 +420,73 @@ pub async fn check_user_liked(
 
 pub async fn get_posts_by_username(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<UsernamePath>>,
     Valid(query): Valid<Query<PostPaginationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
     let client = pool;
-    let (offset, limit, _, _, _) = post_pagination_params(&query);
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
 
-    let (posts, total) =
-        services::post::get_posts_by_username(&client, &params.username, offset, limit).await?;
+    let viewer = auth_user.map(|user| user.id);
+    let posts =
+        services::post::get_posts_by_username(&client, &params.username, &page, viewer).await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved posts",
         posts,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
 pub async fn get_post(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
-) -> Result<Json<ApiResponse<Post>>, AppError> {
+    Valid(query): Valid<Query<PostDetailQuery>>,
+) -> Result<Response, AppError> {
     let client = pool;
-    match services::post::get_post_by_id(&client, params.id).await {
-        Ok(Some(post)) => Ok(Json(ApiResponse::success_with_message(
-            "Successfully retrieved post",
-            post,
-        ))),
+    let access = PostAccess::new(auth_user.map(|user| user.id), query.share_token.as_deref());
+    match services::post::get_post_by_id(&client, params.id, query.render, &access).await {
+        Ok(Some(post)) => Ok(post_with_etag("Successfully retrieved post", post)),
         Ok(None) => Err(AppError::NotFound(format!("Post not found: {}", params.id))),
         Err(e) => Err(AppError::from(e)),
     }
 }
 
+/// A post response carrying its version as the `ETag`.
+fn post_with_etag(message: &str, post: Post) -> Response {
+    (
+        [(header::ETAG, precondition::etag(post.version))],
+        Json(ApiResponse::success_with_message(message, post)),
+    )
+        .into_response()
+}
+
+fn map_update_post_error(err: UpdatePostError) -> AppError {
+    match err {
+        UpdatePostError::Db(err) => AppError::from(err),
+        UpdatePostError::PostNotFound => AppError::NotFound("Post not found".to_string()),
+        UpdatePostError::VersionMismatch { current } => AppError::PreconditionFailed {
+            message: "Post was modified since it was loaded; reload it and retry".to_string(),
+            current_version: current,
+        },
+    }
+}
+
 pub async fn update_post(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
+    if_match: IfMatch,
     Valid(Path(params)): Valid<Path<PostIdPath>>,
     Valid(Json(req)): Valid<Json<UpdatePostRequest>>,
-) -> Result<Json<ApiResponse<Post>>, AppError> {
+) -> Result<Response, AppError> {
     ensure_author(&pool, params.id, &auth_user).await?;
 
-    match services::post::update_post(
+    let post = services::post::update_post(
         &pool,
         params.id,
         services::post::UpdatePostInput {
@@ -367,17 +495,16 @@ pub async fn update_post(
             slug: req.slug,
             body: req.body,
             published: req.published,
+            published_at: req.published_at,
+            visibility: req.visibility,
             tags: req.tags,
         },
+        if_match.expected(),
     )
-    .await?
-    {
-        Some(post) => Ok(Json(ApiResponse::success_with_message(
-            "Post updated successfully",
-            post,
-        ))),
-        None => Err(AppError::NotFound("Post not found".to_string())),
-    }
+    .await
+    .map_err(map_update_post_error)?;
+
+    Ok(post_with_etag("Post updated successfully", post))
 }
 
 pub async fn delete_post(
@@ -396,26 +523,212 @@ pub async fn delete_post(
     }
 }
 
+pub async fn get_post_revisions(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(query): Valid<Query<PaginationQuery>>,
+) -> Result<Json<ApiResponse<Vec<PostRevisionSummary>>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(10);
+    let (revisions, total) =
+        services::post_revision::list_revisions(&pool, params.id, offset, limit).await?;
+
+    Ok(Json(ApiResponse::with_meta_message(
+        "Successfully retrieved post revisions",
+        revisions,
+        total,
+        limit,
+        offset,
+    )))
+}
+
+pub async fn get_post_revision(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostRevisionPath>>,
+) -> Result<Json<ApiResponse<PostRevisionResponse>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+
+    let revision = services::post_revision::get_revision(&pool, params.id, params.revision_id)
+        .await
+        .map_err(map_post_revision_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Successfully retrieved post revision",
+        revision,
+    )))
+}
+
+pub async fn diff_post_revisions(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+    Valid(query): Valid<Query<RevisionDiffQuery>>,
+) -> Result<Json<ApiResponse<PostRevisionDiffResponse>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+
+    let diff = services::post_revision::diff_revisions(
+        &pool,
+        params.id,
+        query.from,
+        query.to,
+        query.granularity.into(),
+    )
+    .await
+    .map_err(map_post_revision_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Successfully compared post revisions",
+        diff,
+    )))
+}
+
+pub async fn restore_post_revision(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostRevisionPath>>,
+) -> Result<Json<ApiResponse<Post>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+
+    let post = services::post_revision::restore_revision(
+        &pool,
+        params.id,
+        params.revision_id,
+        auth_user.id,
+    )
+    .await
+    .map_err(map_post_revision_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Post restored from revision",
+        post,
+    )))
+}
+
+/// A link made under an old slug or username answers with a pointer to the post's current
+/// location instead of a 404.
 pub async fn get_post_by_username_and_slug(
     State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
     Valid(Path(params)): Valid<Path<PostPath>>,
-) -> Result<Json<ApiResponse<Post>>, AppError> {
+    Valid(query): Valid<Query<PostDetailQuery>>,
+) -> Result<Response, AppError> {
     let client = pool;
-    match services::post::get_post_by_username_and_slug(&client, &params.username, &params.slug)
-        .await
+    let access = PostAccess::new(auth_user.map(|user| user.id), query.share_token.as_deref());
+    if let Some(post) = services::post::get_post_by_username_and_slug(
+        &client,
+        &params.username,
+        &params.slug,
+        query.render,
+        &access,
+    )
+    .await?
     {
-        Ok(Some(post)) => Ok(Json(ApiResponse::success_with_message(
-            "Successfully retrieved post",
-            post,
-        ))),
-        Ok(None) => Err(AppError::NotFound(format!(
+        return Ok(post_with_etag("Successfully retrieved post", post));
+    }
+    match services::slug_history::resolve_post_location(&client, &params.username, &params.slug)
+        .await?
+    {
+        Some(redirect) if redirect.moved => Ok(Json(ApiResponse::success_with_message(
+            "Post has moved",
+            redirect,
+        ))
+        .into_response()),
+        _ => Err(AppError::NotFound(format!(
             "Post not found: {} by {}",
             params.slug, params.username
         ))),
-        Err(e) => Err(AppError::from(e)),
     }
 }
 
+/// Resolves a possibly outdated post link: 301 to the current location when it has moved,
+/// otherwise the canonical location itself.
+pub async fn resolve_post(
+    State(pool): State<DbPool>,
+    Valid(Path(params)): Valid<Path<PostPath>>,
+) -> Result<Response, AppError> {
+    let Some(redirect) =
+        services::slug_history::resolve_post_location(&pool, &params.username, &params.slug)
+            .await?
+    else {
+        return Err(AppError::NotFound(format!(
+            "Post not found: {} by {}",
+            params.slug, params.username
+        )));
+    };
+    if redirect.moved {
+        let location = redirect.location.clone();
+        return Ok((
+            StatusCode::MOVED_PERMANENTLY,
+            [(header::LOCATION, location)],
+            Json(ApiResponse::success_with_message(
+                "Post has moved",
+                redirect,
+            )),
+        )
+            .into_response());
+    }
+    Ok(Json(ApiResponse::success_with_message(
+        "Post location resolved",
+        redirect,
+    ))
+    .into_response())
+}
+
+fn map_post_share_error(err: PostShareError) -> AppError {
+    match err {
+        PostShareError::Db(err) => AppError::from(err),
+        PostShareError::PostNotFound => AppError::NotFound("Post not found".to_string()),
+        PostShareError::TokenNotFound => AppError::NotFound("Share token not found".to_string()),
+    }
+}
+
+/// Creates a secret link to the post; the token in the response is not shown again.
+pub async fn create_share_token(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+) -> Result<Json<ApiResponse<ShareTokenResponse>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+    let token = services::post_share::create_share_token(&pool, params.id, auth_user.id)
+        .await
+        .map_err(map_post_share_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Share token created successfully",
+        token,
+    )))
+}
+
+pub async fn get_share_tokens(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+) -> Result<Json<ApiResponse<Vec<ShareTokenResponse>>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+    let tokens = services::post_share::list_share_tokens(&pool, params.id)
+        .await
+        .map_err(map_post_share_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Share tokens fetched successfully",
+        tokens,
+    )))
+}
+
+pub async fn revoke_share_token(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<ShareTokenPath>>,
+) -> Result<Json<ApiResponse<ShareTokenResponse>>, AppError> {
+    ensure_author(&pool, params.id, &auth_user).await?;
+    let token = services::post_share::revoke_share_token(&pool, params.id, params.token_id)
+        .await
+        .map_err(map_post_share_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Share token revoked successfully",
+        token,
+    )))
+}
+
 pub fn routes() -> Router<DbPool> {
     Router::new()
         .route("/api/posts", get(get_posts).post(create_post))
@@ -427,7 +740,27 @@ pub fn routes() -> Router<DbPool> {
             "/api/posts/u/{username}/{slug}",
             get(get_post_by_username_and_slug),
         )
+        .route("/api/posts/resolve/{username}/{slug}", get(resolve_post))
         .route("/api/posts/tag/{tag}", get(get_posts_by_tag))
+        .route("/api/posts/{id}/related", get(get_related_posts))
+        .route("/api/posts/{id}/revisions", get(get_post_revisions))
+        .route("/api/posts/{id}/revisions/diff", get(diff_post_revisions))
+        .route(
+            "/api/posts/{id}/revisions/{revision_id}",
+            get(get_post_revision),
+        )
+        .route(
+            "/api/posts/{id}/revisions/{revision_id}/restore",
+            post(restore_post_revision),
+        )
+        .route(
+            "/api/posts/{id}/share-tokens",
+            get(get_share_tokens).post(create_share_token),
+        )
+        .route(
+            "/api/posts/{id}/share-tokens/{token_id}",
+            delete(revoke_share_token),
+        )
         .route("/api/posts/{id}/view", post(record_view))
         .route("/api/posts/{id}/views", get(get_post_views))
         .route("/api/posts/{id}/view-stats", get(get_post_view_stats))
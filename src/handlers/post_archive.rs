@@ -0,0 +1,96 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::post::{ExportPostsQuery, ImportPostsQuery};
+use crate::error::AppError;
+use crate::models::post_archive::ImportReport;
+use crate::response::ApiResponse;
+use crate::services::{
+    self,
+    post_archive::{ArchiveError, ExportAuthor},
+};
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Multipart, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use axum_valid::Valid;
+
+const MAX_ARCHIVE_BYTES: usize = 20 * 1024 * 1024;
+
+fn map_archive_error(err: ArchiveError) -> AppError {
+    match err {
+        ArchiveError::Db(err) => AppError::from(err),
+        ArchiveError::InvalidArchive(msg) => {
+            AppError::BadRequest(format!("Invalid zip archive: {msg}"))
+        }
+        ArchiveError::UserNotFound => AppError::NotFound("User not found".to_string()),
+    }
+}
+
+/// Imports a zip of Markdown files (multipart field `file`) as posts by the caller.
+pub async fn import_posts(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<ImportPostsQuery>>,
+    multipart: Multipart,
+) -> Result<Json<ApiResponse<ImportReport>>, AppError> {
+    let data = super::upload::read_file_field(multipart, MAX_ARCHIVE_BYTES).await?;
+    let report = services::post_archive::import_posts(&pool, auth_user.id, data, query.dry_run)
+        .await
+        .map_err(map_archive_error)?;
+    let message = if query.dry_run {
+        "Import dry run completed"
+    } else {
+        "Posts imported successfully"
+    };
+    Ok(Json(ApiResponse::success_with_message(message, report)))
+}
+
+pub async fn export_posts(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<ExportPostsQuery>>,
+) -> Result<Response, AppError> {
+    // The caller is always resolved by id: the username in an unrefreshed token may since
+    // have been taken by another user.
+    let author = match query.username.as_deref() {
+        Some(username) if auth_user.username.as_deref() != Some(username) => {
+            if !auth_user.is_super_admin {
+                return Err(AppError::Forbidden(
+                    "Only admins can export another author's posts".to_string(),
+                ));
+            }
+            ExportAuthor::Username(username)
+        }
+        _ => ExportAuthor::Id(auth_user.id),
+    };
+    let (username, archive) = services::post_archive::export_posts(&pool, author)
+        .await
+        .map_err(map_archive_error)?;
+    let file_name = match username {
+        Some(username) => format!("{username}-posts.zip"),
+        None => "posts.zip".to_string(),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route(
+            "/api/posts/import",
+            post(import_posts).layer(DefaultBodyLimit::max(MAX_ARCHIVE_BYTES + 64 * 1024)),
+        )
+        .route("/api/posts/export", get(export_posts))
+}
@@ -0,0 +1,76 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::common::{CursorPaginationQuery, PostIdPath};
+use crate::error::AppError;
+use crate::models::post::Post;
+use crate::pagination::Page;
+use crate::response::ApiResponse;
+use crate::services::{self, post_trash::TrashError};
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    routing::{delete, get, post},
+};
+use axum_valid::Valid;
+
+fn map_trash_error(err: TrashError) -> AppError {
+    match err {
+        TrashError::Db(err) => AppError::from(err),
+        TrashError::PostNotFound => AppError::NotFound("Post not found in trash".to_string()),
+        TrashError::NotOwner => AppError::Forbidden("You are not the post author".to_string()),
+    }
+}
+
+pub async fn get_trash(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<CursorPaginationQuery>>,
+) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let posts = services::post_trash::list_trash(&pool, auth_user.id, &page).await?;
+    Ok(Json(ApiResponse::with_page_message(
+        "Trash fetched successfully",
+        posts,
+        &page,
+    )))
+}
+
+pub async fn restore_post(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+) -> Result<Json<ApiResponse<Post>>, AppError> {
+    let post = services::post_trash::restore_post(&pool, params.id, auth_user.id)
+        .await
+        .map_err(map_trash_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Post restored successfully",
+        post,
+    )))
+}
+
+/// Permanently deletes a post that is already in the trash; live posts must be deleted first.
+pub async fn purge_post(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<PostIdPath>>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    services::post_trash::purge_post(&pool, params.id, auth_user.id)
+        .await
+        .map_err(map_trash_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Post permanently deleted",
+        serde_json::Value::Null,
+    )))
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route("/api/posts/trash", get(get_trash))
+        .route("/api/posts/trash/{id}", delete(purge_post))
+        .route("/api/posts/trash/{id}/restore", post(restore_post))
+}
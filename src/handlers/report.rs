@@ -2,13 +2,15 @@ use crate::auth::AdminUser;
 use crate::database::DbPool;
 use crate::dto::report::{OverviewReport, ReportQuery, date_range};
 use crate::error::AppError;
-use crate::models::report::{EngagementMetricsResponse, PostReportResponse, UserReportResponse};
+use crate::models::report::{
+    CounterReconcileReport, EngagementMetricsResponse, PostReportResponse, UserReportResponse,
+};
 use crate::response::ApiResponse;
 use crate::services;
 use axum::{
     Json, Router,
     extract::{Query, State},
-    routing::get,
+    routing::{get, post},
 };
 use axum_valid::Valid;
 
@@ -72,10 +74,23 @@ pub async fn get_engagement(
     )))
 }
 
+/// Recomputes every engagement counter from its source table and reports the drift found.
+pub async fn reconcile_counters(
+    State(pool): State<DbPool>,
+    _admin_user: AdminUser,
+) -> Result<Json<ApiResponse<CounterReconcileReport>>, AppError> {
+    let report = services::counter::reconcile(&pool).await?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Counters reconciled successfully",
+        report,
+    )))
+}
+
 pub fn routes() -> Router<DbPool> {
     Router::new()
         .route("/api/reports/overview", get(get_overview))
         .route("/api/reports/users", get(get_users))
         .route("/api/reports/posts", get(get_posts))
         .route("/api/reports/engagement", get(get_engagement))
+        .route("/api/reports/counters/reconcile", post(reconcile_counters))
 }
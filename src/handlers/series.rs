@@ -0,0 +1,172 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::common::UsernamePath;
+use crate::dto::series::{
+    AddSeriesPostRequest, CreateSeriesRequest, ReorderSeriesRequest, SeriesIdPath, SeriesPostPath,
+    UpdateSeriesRequest,
+};
+use crate::error::AppError;
+use crate::models::series::SeriesResponse;
+use crate::response::ApiResponse;
+use crate::services::{self, series::SeriesError};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{delete, get, post, put},
+};
+use axum_valid::Valid;
+
+fn map_series_error(err: SeriesError) -> AppError {
+    match err {
+        SeriesError::Db(err) => AppError::from(err),
+        SeriesError::NotFound => AppError::NotFound("Series not found".to_string()),
+        SeriesError::PostNotFound => AppError::NotFound("Post not found".to_string()),
+        SeriesError::PostAlreadyInSeries => {
+            AppError::BadRequest("Post already belongs to a series".to_string())
+        }
+        SeriesError::PostNotInSeries => {
+            AppError::NotFound("Post is not part of this series".to_string())
+        }
+        SeriesError::InvalidOrder => AppError::BadRequest(
+            "post_ids must list every post in the series exactly once".to_string(),
+        ),
+    }
+}
+
+pub async fn create_series(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Json(req)): Valid<Json<CreateSeriesRequest>>,
+) -> Result<(axum::http::StatusCode, Json<ApiResponse<SeriesResponse>>), AppError> {
+    let series = services::series::create_series(&pool, auth_user.id, req.title, req.description)
+        .await
+        .map_err(map_series_error)?;
+    Ok((
+        axum::http::StatusCode::CREATED,
+        Json(ApiResponse::success_with_message(
+            "Series created successfully",
+            series,
+        )),
+    ))
+}
+
+pub async fn get_series(
+    State(pool): State<DbPool>,
+    auth_user: Option<AuthUser>,
+    Valid(Path(params)): Valid<Path<SeriesIdPath>>,
+) -> Result<Json<ApiResponse<SeriesResponse>>, AppError> {
+    let series = services::series::get_series(&pool, params.id, auth_user.map(|user| user.id))
+        .await
+        .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Series fetched successfully",
+        series,
+    )))
+}
+
+pub async fn get_series_by_username(
+    State(pool): State<DbPool>,
+    Valid(Path(params)): Valid<Path<UsernamePath>>,
+) -> Result<Json<ApiResponse<Vec<SeriesResponse>>>, AppError> {
+    let series = services::series::get_series_by_username(&pool, &params.username)
+        .await
+        .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Series fetched successfully",
+        series,
+    )))
+}
+
+pub async fn update_series(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<SeriesIdPath>>,
+    Valid(Json(req)): Valid<Json<UpdateSeriesRequest>>,
+) -> Result<Json<ApiResponse<SeriesResponse>>, AppError> {
+    let series =
+        services::series::update_series(&pool, params.id, auth_user.id, req.title, req.description)
+            .await
+            .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Series updated successfully",
+        series,
+    )))
+}
+
+pub async fn delete_series(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<SeriesIdPath>>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    services::series::delete_series(&pool, params.id, auth_user.id)
+        .await
+        .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Series deleted successfully",
+        serde_json::Value::Null,
+    )))
+}
+
+pub async fn add_series_post(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<SeriesIdPath>>,
+    Valid(Json(req)): Valid<Json<AddSeriesPostRequest>>,
+) -> Result<Json<ApiResponse<SeriesResponse>>, AppError> {
+    let series =
+        services::series::add_post(&pool, params.id, auth_user.id, req.post_id, req.position)
+            .await
+            .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Post added to series",
+        series,
+    )))
+}
+
+pub async fn remove_series_post(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<SeriesPostPath>>,
+) -> Result<Json<ApiResponse<SeriesResponse>>, AppError> {
+    let series = services::series::remove_post(&pool, params.id, auth_user.id, params.post_id)
+        .await
+        .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Post removed from series",
+        series,
+    )))
+}
+
+pub async fn reorder_series_posts(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<SeriesIdPath>>,
+    Valid(Json(req)): Valid<Json<ReorderSeriesRequest>>,
+) -> Result<Json<ApiResponse<SeriesResponse>>, AppError> {
+    let series = services::series::reorder_posts(&pool, params.id, auth_user.id, req.post_ids)
+        .await
+        .map_err(map_series_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Series reordered successfully",
+        series,
+    )))
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route("/api/series", post(create_series))
+        .route(
+            "/api/series/username/{username}",
+            get(get_series_by_username),
+        )
+        .route(
+            "/api/series/{id}",
+            get(get_series).patch(update_series).delete(delete_series),
+        )
+        .route("/api/series/{id}/posts", post(add_series_post))
+        .route("/api/series/{id}/posts/order", put(reorder_series_posts))
+        .route(
+            "/api/series/{id}/posts/{post_id}",
+            delete(remove_series_post),
+        )
+}
@@ -0,0 +1,53 @@
+use crate::config::SiteConfig;
+use crate::database::DbPool;
+use crate::dto::sitemap::SitemapPath;
+use crate::error::AppError;
+use crate::services;
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use axum_valid::Valid;
+
+fn xml_response(body: String) -> Response {
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/xml; charset=utf-8"),
+            ),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=3600"),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+pub async fn get_sitemap_index(State(pool): State<DbPool>) -> Result<Response, AppError> {
+    let body = services::sitemap::index(&pool, SiteConfig::get()).await?;
+    Ok(xml_response(body))
+}
+
+pub async fn get_sitemap(
+    State(pool): State<DbPool>,
+    Valid(Path(params)): Valid<Path<SitemapPath>>,
+) -> Result<Response, AppError> {
+    match services::sitemap::urlset(&pool, SiteConfig::get(), params.kind.into(), params.page)
+        .await?
+    {
+        Some(body) => Ok(xml_response(body)),
+        None => Err(AppError::NotFound("Sitemap not found".to_string())),
+    }
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route("/api/sitemap.xml", get(get_sitemap_index))
+        .route("/api/sitemaps/{kind}/{page}", get(get_sitemap))
+}
@@ -1,14 +1,15 @@
+use crate::auth::AuthUser;
 use crate::database::DbPool;
 use crate::dto::common::PaginationQuery;
 use crate::dto::tag::TagIdPath;
 use crate::error::AppError;
-use crate::models::tag::{SitemapTag, Tag};
+use crate::models::tag::{SitemapTag, Tag, TagFollowResponse};
 use crate::response::ApiResponse;
 use crate::services;
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
-    routing::get,
+    routing::{get, post},
 };
 use axum_valid::Valid;
 
@@ -56,9 +57,53 @@ pub async fn get_tag_by_id(
     }
 }
 
+pub async fn follow_tag(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<TagIdPath>>,
+) -> Result<Json<ApiResponse<TagFollowResponse>>, AppError> {
+    match services::tag::follow_tag(&pool, auth_user.id, params.id).await? {
+        Some(result) => Ok(Json(ApiResponse::success_with_message(
+            "Tag followed successfully",
+            result,
+        ))),
+        None => Err(AppError::NotFound("Tag not found".to_string())),
+    }
+}
+
+pub async fn unfollow_tag(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(params)): Valid<Path<TagIdPath>>,
+) -> Result<Json<ApiResponse<TagFollowResponse>>, AppError> {
+    match services::tag::unfollow_tag(&pool, auth_user.id, params.id).await? {
+        Some(result) => Ok(Json(ApiResponse::success_with_message(
+            "Tag unfollowed successfully",
+            result,
+        ))),
+        None => Err(AppError::NotFound("Tag not found".to_string())),
+    }
+}
+
+pub async fn get_followed_tags(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+) -> Result<Json<ApiResponse<Vec<Tag>>>, AppError> {
+    let tags = services::tag::get_followed_tags(&pool, auth_user.id).await?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Successfully retrieved followed tags",
+        tags,
+    )))
+}
+
 pub fn routes() -> Router<DbPool> {
     Router::new()
         .route("/api/tags", get(get_tags))
         .route("/api/tags/sitemap", get(get_tags_for_sitemap))
+        .route("/api/tags/following", get(get_followed_tags))
         .route("/api/tags/{id}", get(get_tag_by_id))
+        .route(
+            "/api/tags/{id}/follow",
+            post(follow_tag).delete(unfollow_tag),
+        )
 }
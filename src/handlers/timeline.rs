@@ -0,0 +1,41 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::timeline::TimelineQuery;
+use crate::error::AppError;
+use crate::models::post::Post;
+use crate::pagination::Page;
+use crate::response::ApiResponse;
+use crate::services::{self, timeline::TimelineError};
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::get,
+};
+use axum_valid::Valid;
+
+fn map_timeline_error(err: TimelineError) -> AppError {
+    match err {
+        TimelineError::Db(err) => AppError::from(err),
+        TimelineError::InvalidCursor => AppError::BadRequest("Invalid cursor".to_string()),
+    }
+}
+
+pub async fn get_feed(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<TimelineQuery>>,
+) -> Result<Json<ApiResponse<Vec<Post>>>, AppError> {
+    let page = Page::from_query(None, query.limit.unwrap_or(20), query.cursor.as_deref())?;
+    let posts = services::timeline::get_timeline(&pool, auth_user.id, &page)
+        .await
+        .map_err(map_timeline_error)?;
+    Ok(Json(ApiResponse::with_page_message(
+        "Successfully retrieved feed",
+        posts,
+        &page,
+    )))
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new().route("/api/feed", get(get_feed))
+}
@@ -0,0 +1,151 @@
+use crate::auth::AuthUser;
+use crate::database::DbPool;
+use crate::dto::upload::{UploadPath, UploadQuery};
+use crate::error::AppError;
+use crate::models::upload::UploadResponse;
+use crate::response::ApiResponse;
+use crate::services::{
+    self,
+    storage::{self, Storage, StorageError},
+    upload::UploadError,
+};
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use axum_valid::Valid;
+use bytes::{Bytes, BytesMut};
+
+fn map_storage_error(err: StorageError) -> AppError {
+    match err {
+        StorageError::NotFound => AppError::NotFound("File not found".to_string()),
+        StorageError::Backend(err) => {
+            tracing::error!("Storage error: {}", err);
+            AppError::InternalServerError("Storage unavailable".to_string())
+        }
+    }
+}
+
+fn map_upload_error(err: UploadError) -> AppError {
+    match err {
+        UploadError::Db(err) => AppError::from(err),
+        UploadError::Storage(err) => map_storage_error(err),
+        UploadError::UnsupportedType => {
+            AppError::BadRequest("Only JPEG, PNG, GIF and WebP images are accepted".to_string())
+        }
+        UploadError::InvalidImage(msg) => AppError::BadRequest(format!("Invalid image: {msg}")),
+        UploadError::UploadNotFound => AppError::NotFound("Upload not found".to_string()),
+        UploadError::NotOwner => {
+            AppError::Forbidden("You can only delete your own uploads".to_string())
+        }
+    }
+}
+
+/// Reads the `file` field, stopping as soon as it grows past the configured limit.
+pub(super) async fn read_file_field(
+    mut multipart: Multipart,
+    max_bytes: usize,
+) -> Result<Bytes, AppError> {
+    let bad_multipart = |err: axum::extract::multipart::MultipartError| {
+        AppError::BadRequest(format!("Invalid multipart body: {}", err.body_text()))
+    };
+    while let Some(mut field) = multipart.next_field().await.map_err(bad_multipart)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let mut data = BytesMut::new();
+        while let Some(chunk) = field.chunk().await.map_err(bad_multipart)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(AppError::BadRequest(format!(
+                    "File exceeds the {max_bytes} byte upload limit"
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        if data.is_empty() {
+            return Err(AppError::BadRequest("File is empty".to_string()));
+        }
+        return Ok(data.freeze());
+    }
+    Err(AppError::BadRequest(
+        "Missing multipart field \"file\"".to_string(),
+    ))
+}
+
+pub async fn create_upload(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Query(query)): Valid<Query<UploadQuery>>,
+    multipart: Multipart,
+) -> Result<Json<ApiResponse<UploadResponse>>, AppError> {
+    let data = read_file_field(multipart, Storage::get().max_upload_bytes()).await?;
+    let upload = services::upload::create_upload(&pool, auth_user.id, query.purpose, data)
+        .await
+        .map_err(map_upload_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "File uploaded successfully",
+        upload,
+    )))
+}
+
+pub async fn get_upload(
+    State(pool): State<DbPool>,
+    Valid(Path(path)): Valid<Path<UploadPath>>,
+) -> Result<Json<ApiResponse<UploadResponse>>, AppError> {
+    let upload = services::upload::get_upload(&pool, path.id)
+        .await
+        .map_err(map_upload_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Upload fetched successfully",
+        upload,
+    )))
+}
+
+pub async fn delete_upload(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Path(path)): Valid<Path<UploadPath>>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    services::upload::delete_upload(&pool, path.id, auth_user.id)
+        .await
+        .map_err(map_upload_error)?;
+    Ok(Json(ApiResponse::success_with_message(
+        "Upload deleted successfully",
+        serde_json::Value::Null,
+    )))
+}
+
+/// Serves stored files when no `STORAGE_PUBLIC_BASE_URL` fronts the bucket. Keys never change
+/// content, so responses are cacheable forever.
+pub async fn serve_file(Path(key): Path<String>) -> Result<Response, AppError> {
+    if key
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "..")
+    {
+        return Err(AppError::NotFound("File not found".to_string()));
+    }
+    let data = Storage::get().read(&key).await.map_err(map_storage_error)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, storage::content_type_for_key(&key)),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+pub fn routes() -> Router<DbPool> {
+    Router::new()
+        .route(
+            "/api/uploads",
+            // The size limit is enforced while reading the field, from `UPLOAD_MAX_BYTES`.
+            post(create_upload).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/api/uploads/{id}", get(get_upload).delete(delete_upload))
+        .route("/api/uploads/files/{*key}", get(serve_file))
+}
@@ -1,16 +1,18 @@
 use crate::auth::{AdminUser, AuthUser};
 use crate::database::DbPool;
-use crate::dto::common::{PaginationQuery, UsernamePath};
-use crate::dto::user::{FollowRequest, UserIdPath};
+use crate::dto::common::{CursorPaginationQuery, PaginationQuery, UsernamePath};
+use crate::dto::user::{ChangeUsernameRequest, FollowRequest, UserIdPath};
 use crate::error::AppError;
 use crate::models::user::UserResponse;
 use crate::models::user_follow::{FollowResponse, FollowStats};
+use crate::pagination::Page;
 use crate::response::ApiResponse;
-use crate::services;
+use crate::services::{self, user::UserError};
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
-    routing::{delete, get, post},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
 };
 use axum_valid::Valid;
 
@@ -94,20 +96,49 @@ pub async fn delete_user(
     }
 }
 
+/// A renamed user's old username answers with a pointer to the new profile location.
 pub async fn get_by_username(
     State(pool): State<DbPool>,
     Valid(Path(params)): Valid<Path<UsernamePath>>,
-) -> Result<Json<ApiResponse<UserResponse>>, AppError> {
-    match services::user::get_by_username(&pool, &params.username).await {
-        Ok(Some(user)) => Ok(Json(ApiResponse::success_with_message(
+) -> Result<Response, AppError> {
+    if let Some(user) = services::user::get_by_username(&pool, &params.username).await? {
+        return Ok(Json(ApiResponse::success_with_message(
             "Successfully retrieved user",
             user,
-        ))),
-        Ok(None) => Err(AppError::NotFound("User not found".to_string())),
-        Err(e) => Err(AppError::from(e)),
+        ))
+        .into_response());
+    }
+    match services::slug_history::resolve_user_location(&pool, &params.username).await? {
+        Some(redirect) if redirect.moved => Ok(Json(ApiResponse::success_with_message(
+            "User has moved",
+            redirect,
+        ))
+        .into_response()),
+        _ => Err(AppError::NotFound("User not found".to_string())),
     }
 }
 
+pub async fn change_username(
+    State(pool): State<DbPool>,
+    auth_user: AuthUser,
+    Valid(Json(req)): Valid<Json<ChangeUsernameRequest>>,
+) -> Result<Json<ApiResponse<UserResponse>>, AppError> {
+    let user = services::user::change_username(&pool, auth_user.id, &req.username)
+        .await
+        .map_err(|err| match err {
+            UserError::Db(err) => AppError::from(err),
+            UserError::UserNotFound => AppError::NotFound("User not found".to_string()),
+            UserError::UsernameTaken => {
+                AppError::BadRequest("Username is already taken".to_string())
+            }
+        })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        "Username changed successfully",
+        user,
+    )))
+}
+
 pub async fn follow_user(
     State(pool): State<DbPool>,
     auth_user: AuthUser,
@@ -162,38 +193,38 @@ pub async fn check_follow_status(
 pub async fn get_followers(
     State(pool): State<DbPool>,
     Valid(Path(params)): Valid<Path<UserIdPath>>,
-    Valid(query): Valid<Query<PaginationQuery>>,
+    Valid(query): Valid<Query<CursorPaginationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<UserResponse>>>, AppError> {
-    let offset = query.offset.unwrap_or(0);
-    let limit = query.limit.unwrap_or(10);
-    let (followers, total) =
-        services::user_follow::get_followers(&pool, params.id, limit, offset, None).await?;
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let followers = services::user_follow::get_followers(&pool, params.id, &page, None).await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved followers",
         followers,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
 pub async fn get_following(
     State(pool): State<DbPool>,
     Valid(Path(params)): Valid<Path<UserIdPath>>,
-    Valid(query): Valid<Query<PaginationQuery>>,
+    Valid(query): Valid<Query<CursorPaginationQuery>>,
 ) -> Result<Json<ApiResponse<Vec<UserResponse>>>, AppError> {
-    let offset = query.offset.unwrap_or(0);
-    let limit = query.limit.unwrap_or(10);
-    let (following, total) =
-        services::user_follow::get_following(&pool, params.id, limit, offset, None).await?;
+    let page = Page::from_query(
+        query.offset,
+        query.limit.unwrap_or(10),
+        query.cursor.as_deref(),
+    )?;
+    let following = services::user_follow::get_following(&pool, params.id, &page, None).await?;
 
-    Ok(Json(ApiResponse::with_meta_message(
+    Ok(Json(ApiResponse::with_page_message(
         "Successfully retrieved following",
         following,
-        total,
-        limit,
-        offset,
+        &page,
     )))
 }
 
@@ -227,6 +258,7 @@ pub fn routes() -> Router<DbPool> {
     Router::new()
         .route("/api/users", get(get_users))
         .route("/api/users/me", get(get_me))
+        .route("/api/users/me/username", patch(change_username))
         .route("/api/users/username/{username}", get(get_by_username))
         .route("/api/users/follow", post(follow_user))
         .route("/api/users/{id}/follow", delete(unfollow_user))
@@ -0,0 +1,22 @@
+//! Background tasks started alongside the HTTP server.
+
+pub mod scheduled_publish;
+pub mod trash_purge;
+pub mod trending;
+
+use crate::config::JobsConfig;
+use sea_orm::DatabaseConnection;
+
+pub fn spawn_all(db: &DatabaseConnection, config: &JobsConfig) {
+    tokio::spawn(scheduled_publish::run(db.clone(), config.publish_interval));
+    tokio::spawn(trending::run(
+        db.clone(),
+        config.trending_interval,
+        config.trending_gravity,
+    ));
+    tokio::spawn(trash_purge::run(
+        db.clone(),
+        config.trash_purge_interval,
+        config.trash_retention,
+    ));
+}
@@ -0,0 +1,103 @@
+use crate::entities::{user_follows, users};
+use crate::services::notification::{self, NewNotification};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    QuerySelect, Statement, TransactionTrait,
+};
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Posts claimed per tick; the rest are picked up on the next one.
+const BATCH_SIZE: i64 = 100;
+/// Publish moments older than this are marked as handled without notifying anyone, so
+/// backdated posts do not flood followers.
+const STALE_AFTER_HOURS: i64 = 24;
+
+#[derive(FromQueryResult)]
+struct DuePost {
+    id: Uuid,
+    created_by: Uuid,
+    title: String,
+    slug: String,
+    stale: bool,
+    /// Public or followers-only; unlisted and private posts are claimed but never announced.
+    announced: bool,
+}
+
+pub async fn run(db: DatabaseConnection, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match notify_due_posts(&db).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Sent publish notifications for {} posts", count),
+            Err(err) => tracing::error!("Scheduled publish job failed: {}", err),
+        }
+    }
+}
+
+/// Claims posts whose `published_at` has passed and that have not been announced yet, and
+/// notifies the author's followers unless the post is unlisted or private. `SKIP LOCKED`
+/// keeps concurrent instances from sending the same notification twice.
+pub async fn notify_due_posts(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let txn = db.begin().await?;
+    let due = DuePost::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            "UPDATE posts p SET published_notified_at = NOW() \
+             FROM (SELECT id FROM posts WHERE published = true AND deleted_at IS NULL \
+             AND published_at IS NOT NULL AND published_at <= NOW() \
+             AND published_notified_at IS NULL ORDER BY published_at LIMIT $1 \
+             FOR UPDATE SKIP LOCKED) due \
+             WHERE p.id = due.id \
+             RETURNING p.id, p.created_by, p.title, p.slug, \
+             p.published_at < NOW() - INTERVAL '{STALE_AFTER_HOURS} hours' AS stale, \
+             p.visibility IN ('public', 'followers') AS announced"
+        ),
+        [BATCH_SIZE.into()],
+    ))
+    .all(&txn)
+    .await?;
+
+    for post in due.iter().filter(|post| !post.stale && post.announced) {
+        let follower_ids: Vec<Uuid> = user_follows::Entity::find()
+            .select_only()
+            .column(user_follows::Column::FollowerId)
+            .filter(user_follows::Column::FollowingId.eq(post.created_by))
+            .filter(user_follows::Column::DeletedAt.is_null())
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if follower_ids.is_empty() {
+            continue;
+        }
+
+        let author = users::Entity::find_by_id(post.created_by)
+            .one(&txn)
+            .await?
+            .and_then(|user| user.username);
+        let title = match &author {
+            Some(username) => format!("New post from {}", username),
+            None => "New post from someone you follow".to_string(),
+        };
+        notification::notify_users(
+            &txn,
+            &follower_ids,
+            &NewNotification {
+                notification_type: "new_post",
+                title: &title,
+                message: Some(&post.title),
+                data: Some(json!({
+                    "post_id": post.id,
+                    "slug": post.slug,
+                    "username": author,
+                })),
+            },
+        )
+        .await?;
+    }
+
+    txn.commit().await?;
+    Ok(due.len())
+}
@@ -0,0 +1,16 @@
+use crate::services::post_trash;
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+
+/// Hard-deletes posts whose time in the trash has run out.
+pub async fn run(db: DatabaseConnection, interval: Duration, retention: chrono::Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match post_trash::purge_expired(&db, retention).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Purged {} posts from the trash", count),
+            Err(err) => tracing::error!("Trash purge job failed: {}", err),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::services::trending::{self, TrendingWindow};
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+
+/// Rebuilds every trending window on each tick. The first tick fires immediately, so
+/// rankings exist shortly after startup.
+pub async fn run(db: DatabaseConnection, interval: Duration, gravity: f64) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        for window in TrendingWindow::ALL {
+            match trending::refresh(&db, window, gravity).await {
+                Ok(count) => tracing::debug!(
+                    "Ranked {} trending posts for window {}",
+                    count,
+                    window.as_str()
+                ),
+                Err(err) => tracing::error!(
+                    "Trending job failed for window {}: {}",
+                    window.as_str(),
+                    err
+                ),
+            }
+        }
+    }
+}
@@ -5,7 +5,10 @@ pub mod dto;
 pub mod entities;
 pub mod error;
 pub mod handlers;
+pub mod jobs;
 pub mod models;
+pub mod pagination;
+pub mod precondition;
 pub mod rate_limit;
 pub mod response;
 pub mod services;
@@ -1,4 +1,4 @@
-use axumbackend::{config, database, handlers};
+use axumbackend::{config, database, handlers, jobs, services};
 use std::net::SocketAddr;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -25,6 +25,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config = config::Config::from_env();
     config::JwtConfig::init(config.jwt.clone());
+    config::SiteConfig::init(config.site.clone());
+    config::ViewConfig::init(config.views.clone());
+    services::storage::Storage::init(&config.storage)
+        .map_err(|e| format!("Failed to initialize storage: {}", e))?;
 
     // Create connection pool with configuration from environment
     let pool = database::create_pool(&config.database_url, &config.db_pool)
@@ -41,6 +45,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.db_pool.connection_timeout
     );
 
+    jobs::spawn_all(&pool, &config.jobs);
+
     let app = handlers::create_router().with_state(pool);
 
     let addr = format!("0.0.0.0:{}", config.port);
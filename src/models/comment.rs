@@ -1,8 +1,27 @@
 use super::user::UserResponse;
+use crate::services::comment_moderation::CommentStatus;
+use crate::services::comment_reaction::Reaction;
 use chrono::{DateTime, FixedOffset, Utc};
 use serde::Serialize;
 use uuid::Uuid;
 
+/// Shown instead of the text of a deleted comment that still has replies.
+pub const DELETED_PLACEHOLDER: &str = "[deleted]";
+/// Shown instead of the text of a hidden or unapproved comment that still has replies.
+pub const HIDDEN_PLACEHOLDER: &str = "[hidden]";
+
+#[derive(Serialize, Clone)]
+pub struct ReactionCount {
+    pub reaction: Reaction,
+    pub count: i64,
+    /// Whether the caller left this reaction; always false for anonymous requests.
+    pub reacted: bool,
+}
+
+/// Reaction counts of one comment, in `Reaction::ALL` order, omitting unused reactions.
+#[derive(Serialize, Default)]
+pub struct CommentReactions(pub Vec<ReactionCount>);
+
 #[derive(Serialize)]
 pub struct CommentResponse {
     pub id: Uuid,
@@ -10,10 +29,26 @@ pub struct CommentResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_comment_id: Option<Uuid>,
     pub text: String,
+    /// Current edit version; send it back as `If-Match` when updating.
+    pub version: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<UserResponse>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Deleted comments are kept as placeholders while they have replies.
+    pub deleted: bool,
+    pub status: CommentStatus,
+    /// Reactions of every kind.
+    pub reaction_count: i64,
+    pub reactions: Vec<ReactionCount>,
+    /// Whether the caller left any reaction.
+    pub reacted: bool,
+    /// Nesting level in thread listings; 0 for top-level comments.
+    pub depth: u32,
+    /// Visible replies anywhere below this comment.
+    pub reply_count: i64,
+    /// Direct replies, oldest first.
+    pub replies: Vec<CommentResponse>,
 }
 
 fn to_utc(value: Option<DateTime<FixedOffset>>) -> Option<DateTime<Utc>> {
@@ -25,14 +60,63 @@ impl CommentResponse {
         comment: crate::entities::post_comments::Model,
         user: Option<UserResponse>,
     ) -> Self {
+        let deleted = comment.deleted_at.is_some();
         Self {
             id: comment.id,
             post_id: comment.post_id,
             parent_comment_id: comment.parent_comment_id,
-            text: comment.text,
-            user,
+            text: if deleted {
+                DELETED_PLACEHOLDER.to_string()
+            } else {
+                comment.text
+            },
+            version: comment.version,
+            user: if deleted { None } else { user },
             created_at: to_utc(comment.created_at),
             updated_at: to_utc(comment.updated_at),
+            deleted,
+            status: CommentStatus::parse(&comment.status),
+            reaction_count: comment.reaction_count,
+            reactions: Vec::new(),
+            reacted: false,
+            depth: 0,
+            reply_count: 0,
+            replies: Vec::new(),
         }
     }
+
+    /// Whether the comment is shown to everyone: not deleted and approved.
+    pub fn is_visible(&self) -> bool {
+        !self.deleted && self.status == CommentStatus::Approved
+    }
+
+    pub fn with_reactions(mut self, reactions: CommentReactions) -> Self {
+        self.reacted = reactions.0.iter().any(|count| count.reacted);
+        self.reactions = reactions.0;
+        self
+    }
+
+    /// Strips the text, author and reactions of a deleted, hidden or unapproved comment kept
+    /// for its replies.
+    pub fn into_placeholder(mut self) -> Self {
+        self.reaction_count = 0;
+        self.reactions.clear();
+        self.reacted = false;
+        if !self.deleted {
+            self.text = HIDDEN_PLACEHOLDER.to_string();
+            self.user = None;
+        }
+        self
+    }
+
+    /// Attaches `replies` to a comment at `depth`, counting the visible ones beneath it.
+    pub fn with_replies(mut self, depth: u32, replies: Vec<CommentResponse>) -> Self {
+        self.depth = depth;
+        self.reply_count = replies
+            .iter()
+            .map(|reply| i64::from(reply.is_visible()) + reply.reply_count)
+            .sum();
+        self.replies = replies;
+        self
+    }
 }
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct CommentSettingsResponse {
+    pub post_id: Uuid,
+    pub comments_locked: bool,
+    pub comment_approval_required: bool,
+}
+
+impl CommentSettingsResponse {
+    pub fn from_entity(post: &crate::entities::posts::Model) -> Self {
+        Self {
+            post_id: post.id,
+            comments_locked: post.comments_locked,
+            comment_approval_required: post.comment_approval_required,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ModerationLogEntry {
+    pub id: i64,
+    pub post_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<Uuid>,
+    pub moderator_id: Uuid,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModerationLogEntry {
+    pub fn from_entity(model: crate::entities::comment_moderation_log::Model) -> Self {
+        Self {
+            id: model.id,
+            post_id: model.post_id,
+            comment_id: model.comment_id,
+            moderator_id: model.moderator_id,
+            action: model.action,
+            reason: model.reason,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
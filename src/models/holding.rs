@@ -1,6 +1,7 @@
 use chrono::{DateTime, FixedOffset, Utc};
 use sea_orm::prelude::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -36,6 +37,17 @@ pub struct HoldingResponse {
     pub year: i32,
 }
 
+#[derive(Serialize)]
+pub struct HoldingAuditResponse {
+    pub id: i64,
+    pub holding_id: i64,
+    pub actor_id: Uuid,
+    pub action: String,
+    pub changes: Option<Value>,
+    pub snapshot: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HoldingSummaryResponse {
@@ -170,6 +182,81 @@ pub struct DuplicateResultItem {
     pub year: i32,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingPositionPoint {
+    pub month: i32,
+    pub year: i32,
+    pub date: String,
+    pub units: Option<String>,
+    pub current_price: Option<String>,
+    pub invested_amount: String,
+    pub current_value: String,
+    pub gain_amount: String,
+    pub gain_percent: String,
+    pub rows: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingPositionHistoryResponse {
+    pub symbol: Option<String>,
+    pub name: String,
+    pub platform: Option<String>,
+    pub currency: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub months_held: i64,
+    pub history: Vec<HoldingPositionPoint>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingProjectionBucket {
+    pub name: String,
+    pub current_value: f64,
+    pub expected_return: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingProjectionRange {
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingProjectionPoint {
+    pub year: i32,
+    pub years_from_now: u32,
+    pub contributed: f64,
+    pub nominal_value: f64,
+    pub real_value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nominal_range: Option<HoldingProjectionRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub real_range: Option<HoldingProjectionRange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingProjectionResponse {
+    pub month: i32,
+    pub year: i32,
+    pub mode: String,
+    pub starting_value: f64,
+    pub monthly_contribution: f64,
+    pub inflation: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annual_volatility: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulations: Option<u32>,
+    pub buckets: Vec<HoldingProjectionBucket>,
+    pub series: Vec<HoldingProjectionPoint>,
+}
+
 fn decimal_to_string(value: Decimal) -> String {
     value.normalize().to_string()
 }
@@ -197,6 +284,24 @@ impl From<crate::entities::holding_types::Model> for HoldingTypeResponse {
     }
 }
 
+impl From<crate::entities::holding_audits::Model> for HoldingAuditResponse {
+    fn from(model: crate::entities::holding_audits::Model) -> Self {
+        let parse = |payload: Option<String>| {
+            payload.and_then(|payload| serde_json::from_str(&payload).ok())
+        };
+
+        Self {
+            id: model.id,
+            holding_id: model.holding_id,
+            actor_id: model.actor_id,
+            action: model.action,
+            changes: parse(model.changes),
+            snapshot: parse(model.snapshot),
+            created_at: to_utc(model.created_at),
+        }
+    }
+}
+
 impl HoldingResponse {
     pub fn from_entity(
         holding: crate::entities::holdings::Model,
@@ -239,3 +344,40 @@ impl HoldingResponse {
         }
     }
 }
+
+impl HoldingPositionPoint {
+    pub fn from_totals(
+        month: i32,
+        year: i32,
+        units: Option<Decimal>,
+        current_price: Option<Decimal>,
+        invested_amount: Decimal,
+        current_value: Decimal,
+        rows: i64,
+    ) -> Self {
+        let gain_amount = current_value - invested_amount;
+        let gain_percent = if invested_amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            (gain_amount / invested_amount * Decimal::new(100, 0)).round_dp(2)
+        };
+        let current_price = current_price.or_else(|| {
+            units
+                .filter(|units| !units.is_zero())
+                .map(|units| (current_value / units).round_dp(8))
+        });
+
+        Self {
+            month,
+            year,
+            date: format!("{:04}-{:02}", year, month),
+            units: maybe_decimal_to_string(units),
+            current_price: maybe_decimal_to_string(current_price),
+            invested_amount: decimal_to_string(invested_amount),
+            current_value: decimal_to_string(current_value),
+            gain_amount: decimal_to_string(gain_amount),
+            gain_percent: decimal_to_string(gain_percent),
+            rows,
+        }
+    }
+}
@@ -1,11 +1,18 @@
 pub mod bookmark;
 pub mod comment;
+pub mod comment_moderation;
 pub mod holding;
 pub mod notification;
 pub mod post;
+pub mod post_archive;
 pub mod post_like;
+pub mod post_revision;
+pub mod post_share;
 pub mod post_view;
+pub mod redirect;
 pub mod report;
+pub mod series;
 pub mod tag;
+pub mod upload;
 pub mod user;
 pub mod user_follow;
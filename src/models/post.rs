@@ -1,5 +1,7 @@
+use super::series::PostSeriesNav;
 use super::tag::Tag;
 use super::user::User;
+use crate::services::visibility::PostVisibility;
 use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -24,11 +26,48 @@ pub struct Post {
     pub updated_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
     pub published: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    pub visibility: PostVisibility,
+    /// Current edit version; send it back as `If-Match` when updating.
+    pub version: i32,
+    /// New comments are rejected while set.
+    pub comments_locked: bool,
     pub view_count: i64,
     pub like_count: i64,
     pub bookmark_count: i64,
     pub user: Option<User>,
     pub tags: Vec<Tag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<PostSearchMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<RenderedBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<PostSeriesNav>,
+}
+
+/// `body` rendered from Markdown, returned on post detail requests with `?render=true`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RenderedBody {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+    pub word_count: i32,
+    pub reading_time_minutes: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TocEntry {
+    pub level: u8,
+    pub id: String,
+    pub text: String,
+}
+
+/// Present on posts returned by a `search` query. `title` and `snippet` wrap matched terms
+/// in `<mark>` tags.
+#[derive(Serialize, Deserialize)]
+pub struct PostSearchMatch {
+    pub rank: f32,
+    pub title: String,
+    pub snippet: String,
 }
 
 fn to_utc(value: Option<DateTime<FixedOffset>>) -> Option<DateTime<Utc>> {
@@ -61,11 +100,18 @@ impl Post {
             updated_at: to_utc(post.updated_at),
             deleted_at: to_utc(post.deleted_at),
             published: post.published.unwrap_or(true),
+            published_at: to_utc(post.published_at),
+            visibility: PostVisibility::parse(&post.visibility),
+            version: post.version,
+            comments_locked: post.comments_locked,
             view_count: post.view_count.unwrap_or_default(),
             like_count: post.like_count.unwrap_or_default(),
             bookmark_count: post.bookmark_count.unwrap_or_default(),
             user: user.map(Into::into),
             tags: tags.into_iter().map(Into::into).collect(),
+            search: None,
+            rendered: None,
+            series: None,
         }
     }
 }
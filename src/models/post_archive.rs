@@ -0,0 +1,35 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Passed validation in a dry run.
+    Valid,
+    Created,
+    Invalid,
+    /// Valid, but creating the post failed.
+    Failed,
+}
+
+#[derive(Serialize)]
+pub struct ImportEntryReport {
+    pub file: String,
+    pub slug: Option<String>,
+    pub status: ImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub total: usize,
+    pub valid: usize,
+    pub created: usize,
+    pub invalid: usize,
+    pub failed: usize,
+    pub entries: Vec<ImportEntryReport>,
+}
@@ -22,9 +22,14 @@ pub struct PostLikeStats {
 #[derive(Serialize)]
 pub struct PostLikeListResponse {
     pub likes: Vec<PostLikeResponse>,
-    pub total: i64,
+    /// `total` and `offset` are omitted when paging by cursor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
     pub limit: i64,
-    pub offset: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize)]
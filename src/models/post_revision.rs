@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct PostRevisionSummary {
+    pub id: i64,
+    pub post_id: Uuid,
+    pub revision_number: i32,
+    pub title: String,
+    pub slug: String,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct PostRevisionResponse {
+    pub id: i64,
+    pub post_id: Uuid,
+    pub revision_number: i32,
+    pub title: String,
+    pub body: Option<String>,
+    pub slug: String,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A run of consecutive lines or words that were kept, inserted or deleted.
+#[derive(Serialize)]
+pub struct DiffSegment {
+    pub op: &'static str,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct FieldDiff {
+    pub changed: bool,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub segments: Vec<DiffSegment>,
+}
+
+#[derive(Serialize)]
+pub struct PostRevisionDiffResponse {
+    pub post_id: Uuid,
+    pub from: PostRevisionSummary,
+    pub to: PostRevisionSummary,
+    pub granularity: &'static str,
+    pub title: FieldDiff,
+    pub slug: FieldDiff,
+    pub body: FieldDiff,
+}
+
+impl From<crate::entities::post_revisions::Model> for PostRevisionSummary {
+    fn from(model: crate::entities::post_revisions::Model) -> Self {
+        Self {
+            id: model.id,
+            post_id: model.post_id,
+            revision_number: model.revision_number,
+            title: model.title,
+            slug: model.slug,
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
+
+impl From<crate::entities::post_revisions::Model> for PostRevisionResponse {
+    fn from(model: crate::entities::post_revisions::Model) -> Self {
+        Self {
+            id: model.id,
+            post_id: model.post_id,
+            revision_number: model.revision_number,
+            title: model.title,
+            body: model.body,
+            slug: model.slug,
+            created_by: model.created_by,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct ShareTokenResponse {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    /// The secret itself, returned only when the token is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ShareTokenResponse {
+    pub fn from_entity(model: crate::entities::post_share_tokens::Model) -> Self {
+        Self {
+            id: model.id,
+            post_id: model.post_id,
+            token: None,
+            url: None,
+            created_at: model.created_at.with_timezone(&Utc),
+            revoked_at: model.revoked_at.map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
+}
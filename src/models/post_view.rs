@@ -16,6 +16,8 @@ pub struct PostViewResponse {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// `total_views` and the breakdowns below it exclude bot and duplicate views;
+/// `unfiltered_views` counts every recorded request.
 #[derive(Serialize)]
 pub struct PostViewStats {
     pub post_id: Uuid,
@@ -23,6 +25,9 @@ pub struct PostViewStats {
     pub unique_views: i64,
     pub anonymous_views: i64,
     pub authenticated_views: i64,
+    pub unfiltered_views: i64,
+    pub bot_views: i64,
+    pub duplicate_views: i64,
 }
 
 #[derive(Serialize)]
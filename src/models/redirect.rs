@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+/// Where a link made under an old username or slug now points.
+#[derive(Serialize)]
+pub struct RedirectResponse {
+    pub moved: bool,
+    pub location: String,
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+}
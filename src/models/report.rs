@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -107,3 +108,21 @@ pub struct EngagementMetricsResponse {
     pub avg_views_per_post: f64,
     pub period_comparison: PeriodComparison,
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CounterDrift {
+    /// `table.column`, e.g. `posts.like_count`.
+    pub counter: String,
+    pub rows_corrected: i64,
+    /// Sum of absolute differences between the stored and recomputed values.
+    pub total_drift: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CounterReconcileReport {
+    pub counters: Vec<CounterDrift>,
+    pub total_rows_corrected: i64,
+    pub reconciled_at: DateTime<Utc>,
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct SeriesPostItem {
+    pub id: Uuid,
+    pub title: String,
+    pub slug: String,
+    pub position: i32,
+    pub published: bool,
+}
+
+#[derive(Serialize)]
+pub struct SeriesResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub post_count: i64,
+    pub posts: Vec<SeriesPostItem>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SeriesPostLink {
+    pub id: Uuid,
+    pub title: String,
+    pub slug: String,
+}
+
+/// Where a post sits in its series, attached to post detail responses.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostSeriesNav {
+    pub id: Uuid,
+    pub title: String,
+    pub position: i32,
+    pub total: i64,
+    pub previous: Option<SeriesPostLink>,
+    pub next: Option<SeriesPostLink>,
+}
+
+fn to_utc(value: Option<DateTime<FixedOffset>>) -> Option<DateTime<Utc>> {
+    value.map(|dt| dt.with_timezone(&Utc))
+}
+
+impl SeriesResponse {
+    pub fn from_entity(
+        series: crate::entities::series::Model,
+        username: Option<String>,
+        posts: Vec<SeriesPostItem>,
+    ) -> Self {
+        Self {
+            id: series.id,
+            user_id: series.user_id,
+            username,
+            title: series.title,
+            description: series.description,
+            post_count: posts.len() as i64,
+            posts,
+            created_at: to_utc(series.created_at),
+            updated_at: to_utc(series.updated_at),
+        }
+    }
+}
@@ -14,6 +14,12 @@ pub struct Tag {
     pub created_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct TagFollowResponse {
+    pub tag: Tag,
+    pub following: bool,
+}
+
 fn to_utc(value: Option<DateTime<FixedOffset>>) -> Option<DateTime<Utc>> {
     value.map(|dt| dt.with_timezone(&Utc))
 }
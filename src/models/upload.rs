@@ -0,0 +1,68 @@
+use crate::services::storage::Storage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// One entry of `uploads.variants`.
+#[derive(Serialize, Deserialize)]
+pub struct StoredVariant {
+    pub name: String,
+    pub key: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize)]
+pub struct UploadVariantResponse {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// `url` and any variant URL can be stored in a post's `photo_url` or a user's `image`.
+#[derive(Serialize)]
+pub struct UploadResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: String,
+    pub content_type: String,
+    pub byte_size: i64,
+    pub width: i32,
+    pub height: i32,
+    pub url: String,
+    pub variants: BTreeMap<String, UploadVariantResponse>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UploadResponse {
+    pub fn from_entity(model: crate::entities::uploads::Model, storage: &Storage) -> Self {
+        let variants = serde_json::from_str::<Vec<StoredVariant>>(&model.variants)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|variant| {
+                (
+                    variant.name,
+                    UploadVariantResponse {
+                        url: storage.url(&variant.key),
+                        width: variant.width,
+                        height: variant.height,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            id: model.id,
+            user_id: model.user_id,
+            purpose: model.purpose,
+            content_type: model.content_type,
+            byte_size: model.byte_size,
+            width: model.width,
+            height: model.height,
+            url: storage.url(&model.storage_key),
+            variants,
+            created_at: model.created_at.with_timezone(&Utc),
+        }
+    }
+}
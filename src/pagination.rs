@@ -0,0 +1,234 @@
+use crate::error::AppError;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, sea_query::NullOrdering,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Keyset position: the `(created_at, id)` of the last row of the previous page. Ranked
+/// listings also carry that row's `rank`, with `created_at` holding the moment the ranking was
+/// taken. Clients only ever see it base64-encoded.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct Cursor {
+    created_at: DateTime<Utc>,
+    id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rank: Option<f64>,
+}
+
+impl Cursor {
+    pub fn new(created_at: impl Into<DateTime<Utc>>, id: Uuid) -> Self {
+        Self {
+            created_at: created_at.into(),
+            id,
+            rank: None,
+        }
+    }
+
+    pub fn ranked(as_of: DateTime<Utc>, rank: f64, id: Uuid) -> Self {
+        Self {
+            created_at: as_of,
+            id,
+            rank: Some(rank),
+        }
+    }
+
+    /// `(as_of, rank, id)` of a cursor built by `Cursor::ranked`.
+    pub fn rank_position(&self) -> Option<(DateTime<Utc>, f64, Uuid)> {
+        Some((self.created_at, self.rank?, self.id))
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(raw: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(raw).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Rows strictly after this position in `(created_at DESC, id DESC)` order.
+    fn after(&self, created_at: impl ColumnTrait, id: impl ColumnTrait) -> Condition {
+        Condition::any().add(created_at.lt(self.created_at)).add(
+            Condition::all()
+                .add(created_at.eq(self.created_at))
+                .add(id.lt(self.id)),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Page {
+    /// Legacy mode: counts the full result set so `Meta` can report totals.
+    Offset { offset: i64, limit: i64 },
+    /// Keyset mode: no `count()`, stable under concurrent inserts.
+    After { cursor: Cursor, limit: i64 },
+}
+
+impl Page {
+    pub fn from_query(
+        offset: Option<i64>,
+        limit: i64,
+        cursor: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let Some(raw) = cursor.filter(|raw| !raw.is_empty()) else {
+            return Ok(Self::Offset {
+                offset: offset.unwrap_or(0).max(0),
+                limit,
+            });
+        };
+        if offset.is_some() {
+            return Err(AppError::BadRequest(
+                "cursor and offset cannot be used together".to_string(),
+            ));
+        }
+        let cursor = Cursor::decode(raw)
+            .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?;
+        Ok(Self::After { cursor, limit })
+    }
+
+    pub fn limit(&self) -> i64 {
+        match *self {
+            Self::Offset { limit, .. } | Self::After { limit, .. } => limit.max(0),
+        }
+    }
+
+    pub fn offset(&self) -> Option<i64> {
+        match *self {
+            Self::Offset { offset, .. } => Some(offset),
+            Self::After { .. } => None,
+        }
+    }
+}
+
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    /// Only computed in offset mode.
+    pub total: Option<i64>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Paged<T> {
+    /// Result for a listing whose parent (user, tag, ...) does not exist.
+    pub fn empty(page: &Page) -> Self {
+        Self {
+            items: Vec::new(),
+            total: page.offset().map(|_| 0),
+            next_cursor: None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paged<U> {
+        Paged {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Fetches one page of `query` newest first by `(created_at, id)`. `cursor_of` builds the
+/// position of a row; rows without a `created_at` sort last and end the cursor chain.
+pub async fn fetch_page<E, C>(
+    db: &DatabaseConnection,
+    query: Select<E>,
+    (created_at, id): (C, C),
+    page: &Page,
+    cursor_of: impl Fn(&E::Model) -> Option<Cursor>,
+) -> Result<Paged<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    C: ColumnTrait,
+{
+    let total = match page {
+        Page::Offset { .. } => Some(query.clone().count(db).await? as i64),
+        Page::After { .. } => None,
+    };
+
+    let query = query
+        .order_by_with_nulls(created_at, Order::Desc, NullOrdering::Last)
+        .order_by_desc(id);
+    let query = match page {
+        Page::Offset { offset, .. } => query.offset(*offset as u64),
+        Page::After { cursor, .. } => query.filter(cursor.after(created_at, id)),
+    };
+
+    let limit = page.limit() as usize;
+    let mut items = query.limit(limit as u64 + 1).all(db).await?;
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items
+            .last()
+            .and_then(cursor_of)
+            .map(|cursor| cursor.encode())
+    } else {
+        None
+    };
+
+    Ok(Paged {
+        items,
+        total,
+        next_cursor,
+    })
+}
+
+/// Offset-only fetch for queries whose ordering cannot be expressed as a keyset (search
+/// relevance, arbitrary sort columns). Never yields a `next_cursor`.
+pub async fn fetch_offset<E>(
+    db: &DatabaseConnection,
+    query: Select<E>,
+    page: &Page,
+) -> Result<Paged<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let total = query.clone().count(db).await? as i64;
+    let items = query
+        .limit(page.limit() as u64)
+        .offset(page.offset().unwrap_or(0) as u64)
+        .all(db)
+        .await?;
+    Ok(Paged {
+        items,
+        total: Some(total),
+        next_cursor: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cursor, Page};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[test]
+    fn cursor_round_trips_and_rejects_offset_combination() {
+        let cursor = Cursor::new(Utc::now(), Uuid::new_v4());
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("not-a-cursor"), None);
+        assert_eq!(cursor.rank_position(), None);
+
+        let ranked = Cursor::ranked(Utc::now(), 38_512.123_456_789_01, Uuid::new_v4());
+        assert_eq!(Cursor::decode(&ranked.encode()), Some(ranked));
+        assert!(ranked.rank_position().is_some());
+
+        let encoded = cursor.encode();
+        assert!(matches!(
+            Page::from_query(None, 10, Some(&encoded)),
+            Ok(Page::After { limit: 10, .. })
+        ));
+        assert!(Page::from_query(Some(20), 10, Some(&encoded)).is_err());
+        assert!(matches!(
+            Page::from_query(Some(20), 10, None),
+            Ok(Page::Offset {
+                offset: 20,
+                limit: 10
+            })
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::AppError;
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+/// Strong `ETag` value for a resource version.
+pub fn etag(version: i32) -> String {
+    format!("\"{version}\"")
+}
+
+/// The `If-Match` header of a write. Required: a request without it is answered with 428 so
+/// clients cannot overwrite concurrent edits by accident. `*` matches any version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfMatch {
+    Any,
+    Version(i32),
+}
+
+impl IfMatch {
+    /// The version the client expects to replace, `None` for `*`.
+    pub fn expected(self) -> Option<i32> {
+        match self {
+            Self::Any => None,
+            Self::Version(version) => Some(version),
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value == "*" {
+            return Some(Self::Any);
+        }
+        value
+            .strip_prefix("W/")
+            .unwrap_or(value)
+            .strip_prefix('"')
+            .and_then(|tag| tag.strip_suffix('"'))
+            .and_then(|tag| tag.parse().ok())
+            .map(Self::Version)
+    }
+}
+
+impl<S> FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Err(AppError::PreconditionRequired(
+                "If-Match header with the current ETag is required".to_string(),
+            ));
+        };
+        value
+            .to_str()
+            .ok()
+            .and_then(Self::parse)
+            .ok_or_else(|| AppError::BadRequest("Invalid If-Match header".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_strong_weak_and_wildcard_tags() {
+        assert_eq!(IfMatch::parse("\"3\""), Some(IfMatch::Version(3)));
+        assert_eq!(IfMatch::parse("W/\"12\""), Some(IfMatch::Version(12)));
+        assert_eq!(IfMatch::parse("*"), Some(IfMatch::Any));
+        assert_eq!(IfMatch::parse("3"), None);
+    }
+}
@@ -1,24 +1,40 @@
+use crate::pagination::{Page, Paged};
 use serde::{Deserialize, Serialize};
 
+/// Totals are only reported in offset mode; cursor-paged responses carry `next_cursor` instead.
 #[derive(Serialize, Deserialize)]
 pub struct Meta {
-    pub total_items: i64,
-    pub offset: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
     pub limit: i64,
-    pub total_pages: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl Default for Meta {
     fn default() -> Self {
         Meta {
-            total_items: 0,
-            offset: 0,
+            total_items: Some(0),
+            offset: Some(0),
             limit: 10,
-            total_pages: 0,
+            total_pages: Some(0),
+            next_cursor: None,
         }
     }
 }
 
+fn total_pages(total: i64, limit: i64) -> i64 {
+    if limit > 0 {
+        (total as f64 / limit as f64).ceil() as i64
+    } else {
+        0
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
@@ -49,22 +65,36 @@ impl<T> ApiResponse<T> {
         limit: i64,
         offset: i64,
     ) -> Self {
-        let total_pages = if limit > 0 {
-            (total as f64 / limit as f64).ceil() as i64
-        } else {
-            0
-        };
-
         ApiResponse {
             success: true,
             message: message.into(),
             data: Some(data),
             error: None,
             meta: Some(Meta {
-                total_items: total,
-                offset,
+                total_items: Some(total),
+                offset: Some(offset),
+                limit,
+                total_pages: Some(total_pages(total, limit)),
+                next_cursor: None,
+            }),
+        }
+    }
+}
+
+impl<T> ApiResponse<Vec<T>> {
+    pub fn with_page_message(message: impl Into<String>, paged: Paged<T>, page: &Page) -> Self {
+        let limit = page.limit();
+        ApiResponse {
+            success: true,
+            message: message.into(),
+            data: Some(paged.items),
+            error: None,
+            meta: Some(Meta {
+                total_items: paged.total,
+                offset: page.offset(),
                 limit,
-                total_pages,
+                total_pages: paged.total.map(|total| total_pages(total, limit)),
+                next_cursor: paged.next_cursor,
             }),
         }
     }
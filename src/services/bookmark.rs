@@ -1,10 +1,12 @@
 use crate::entities::{bookmark_folders, post_bookmarks, posts};
 use crate::models::bookmark::{BookmarkFolderResponse, BookmarkResponse, ToggleBookmarkResponse};
 use crate::models::post::Post;
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::counter::{self, PostCounter};
 use chrono::Utc;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
-    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
@@ -102,9 +104,18 @@ pub async fn toggle_bookmark(
         .one(db)
         .await?
     {
-        post_bookmarks::Entity::delete_by_id(existing.id)
-            .exec(db)
+        let txn = db.begin().await?;
+        let result = post_bookmarks::Entity::delete_by_id(existing.id)
+            .exec(&txn)
             .await?;
+        counter::adjust_post(
+            &txn,
+            post_id,
+            PostCounter::Bookmarks,
+            -(result.rows_affected as i64),
+        )
+        .await?;
+        txn.commit().await?;
         return Ok(ToggleBookmarkResponse {
             action: "removed".to_string(),
             bookmark: None,
@@ -112,6 +123,7 @@ pub async fn toggle_bookmark(
     }
 
     let now = Utc::now();
+    let txn = db.begin().await?;
     let bookmark = post_bookmarks::ActiveModel {
         id: Set(Uuid::new_v4()),
         post_id: Set(post_id),
@@ -122,8 +134,10 @@ pub async fn toggle_bookmark(
         created_at: Set(Some(now.into())),
         updated_at: Set(Some(now.into())),
     }
-    .insert(db)
+    .insert(&txn)
     .await?;
+    counter::adjust_post(&txn, post_id, PostCounter::Bookmarks, 1).await?;
+    txn.commit().await?;
 
     Ok(ToggleBookmarkResponse {
         action: "added".to_string(),
@@ -135,9 +149,8 @@ pub async fn get_bookmarks_by_user(
     db: &DatabaseConnection,
     user_id: Uuid,
     folder_id: Option<Option<Uuid>>,
-    limit: i64,
-    offset: i64,
-) -> Result<(Vec<BookmarkResponse>, i64), BookmarkError> {
+    page: &Page,
+) -> Result<Paged<BookmarkResponse>, BookmarkError> {
     let mut query =
         post_bookmarks::Entity::find().filter(post_bookmarks::Column::UserId.eq(user_id));
     if let Some(folder_filter) = folder_id {
@@ -146,18 +159,30 @@ pub async fn get_bookmarks_by_user(
             None => query = query.filter(post_bookmarks::Column::FolderId.is_null()),
         }
     }
-    let total = query.clone().count(db).await? as i64;
-    let models = query
-        .order_by_desc(post_bookmarks::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-    let mut out = Vec::with_capacity(models.len());
-    for model in models {
+    let paged = pagination::fetch_page(
+        db,
+        query,
+        (
+            post_bookmarks::Column::CreatedAt,
+            post_bookmarks::Column::Id,
+        ),
+        page,
+        |bookmark| {
+            bookmark
+                .created_at
+                .map(|created_at| Cursor::new(created_at, bookmark.id))
+        },
+    )
+    .await?;
+    let mut out = Vec::with_capacity(paged.items.len());
+    for model in paged.items {
         out.push(hydrate_bookmark(db, model).await?);
     }
-    Ok((out, total))
+    Ok(Paged {
+        items: out,
+        total: paged.total,
+        next_cursor: paged.next_cursor,
+    })
 }
 
 pub async fn update_bookmark(
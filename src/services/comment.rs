@@ -1,20 +1,45 @@
-use crate::entities::{post_comments, posts};
-use crate::models::comment::CommentResponse;
+use crate::entities::post_comments;
+use crate::models::comment::{CommentReactions, CommentResponse};
 use crate::models::user::UserResponse;
-use crate::services::user_hydration;
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::comment_moderation::{self, CommentStatus};
+use crate::services::visibility::{self, PostAccess};
+use crate::services::{comment_reaction, user_hydration};
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
-    QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, Set, prelude::Expr, sea_query::SimpleExpr,
 };
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Order of top-level threads and of the replies within them.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentSort {
+    /// Threads newest first, replies oldest first.
+    #[default]
+    Newest,
+    /// Most reactions first at every level, ties broken by the `Newest` order.
+    Top,
+}
+
 #[derive(Debug)]
 pub enum CommentError {
     Db(DbErr),
     PostNotFound,
+    /// The post's comments are locked by a moderator.
+    CommentsLocked,
     CommentNotFound,
+    /// The comment being replied to is missing, deleted or on another post.
+    ParentNotFound,
     NotOwner,
+    /// The comment changed since the client read it.
+    VersionMismatch {
+        current: i32,
+    },
 }
 
 impl From<DbErr> for CommentError {
@@ -23,47 +48,101 @@ impl From<DbErr> for CommentError {
     }
 }
 
-async fn post_exists(db: &DatabaseConnection, post_id: Uuid) -> Result<bool, DbErr> {
-    Ok(posts::Entity::find_by_id(post_id)
-        .filter(posts::Column::DeletedAt.is_null())
-        .one(db)
-        .await?
-        .is_some())
+/// Authors and reactions for a set of comments, as seen by `viewer`.
+struct CommentContext {
+    users_by_id: HashMap<Uuid, UserResponse>,
+    reactions: HashMap<Uuid, CommentReactions>,
 }
 
-fn hydrate_comment(
-    comment: post_comments::Model,
-    users_by_id: &std::collections::HashMap<Uuid, UserResponse>,
-) -> CommentResponse {
-    let user_response = users_by_id.get(&comment.created_by).cloned();
-    CommentResponse::from_entity(comment, user_response)
+async fn load_comment_context(
+    db: &DatabaseConnection,
+    comments: &[post_comments::Model],
+    viewer: Option<Uuid>,
+) -> Result<CommentContext, DbErr> {
+    let users_by_id = user_hydration::load_user_response_map(
+        db,
+        comments.iter().map(|comment| comment.created_by),
+    )
+    .await?;
+    let ids: Vec<Uuid> = comments.iter().map(|comment| comment.id).collect();
+    let reactions = comment_reaction::load_reactions(db, &ids, viewer).await?;
+    Ok(CommentContext {
+        users_by_id,
+        reactions,
+    })
+}
+
+fn hydrate_comment(comment: post_comments::Model, context: &mut CommentContext) -> CommentResponse {
+    let user_response = context.users_by_id.get(&comment.created_by).cloned();
+    let reactions = context.reactions.remove(&comment.id).unwrap_or_default();
+    CommentResponse::from_entity(comment, user_response).with_reactions(reactions)
 }
 
-async fn load_comment_user_map(
+/// Comments with their authors and reactions, without replies.
+pub(crate) async fn hydrate_comments(
     db: &DatabaseConnection,
-    comments: &[post_comments::Model],
-) -> Result<std::collections::HashMap<Uuid, UserResponse>, DbErr> {
-    user_hydration::load_user_response_map(db, comments.iter().map(|comment| comment.created_by))
-        .await
+    comments: Vec<post_comments::Model>,
+    viewer: Option<Uuid>,
+) -> Result<Vec<CommentResponse>, DbErr> {
+    let mut context = load_comment_context(db, &comments, viewer).await?;
+    Ok(comments
+        .into_iter()
+        .map(|comment| hydrate_comment(comment, &mut context))
+        .collect())
+}
+
+async fn hydrate_one(
+    db: &DatabaseConnection,
+    comment: post_comments::Model,
+    viewer: Uuid,
+) -> Result<CommentResponse, CommentError> {
+    hydrate_comments(db, vec![comment], Some(viewer))
+        .await?
+        .pop()
+        .ok_or(CommentError::CommentNotFound)
 }
 
+/// Creates a top-level comment, or a reply when `parent_comment_id` is set. The comment waits
+/// for approval when the post requires it for first-time commenters. Posts the commenter may not
+/// read are reported as missing.
 pub async fn create_comment(
     db: &DatabaseConnection,
     post_id: Uuid,
     text: String,
+    parent_comment_id: Option<Uuid>,
     created_by: Uuid,
+    share_token: Option<&str>,
 ) -> Result<CommentResponse, CommentError> {
-    if !post_exists(db, post_id).await? {
+    let access = PostAccess::new(Some(created_by), share_token);
+    let Some(post) = visibility::find_viewable(db, post_id, &access).await? else {
         return Err(CommentError::PostNotFound);
+    };
+    if post.comments_locked {
+        return Err(CommentError::CommentsLocked);
+    }
+    if let Some(parent_id) = parent_comment_id
+        && post_comments::Entity::find_by_id(parent_id)
+            .filter(post_comments::Column::PostId.eq(post_id))
+            .filter(post_comments::Column::DeletedAt.is_null())
+            .filter(post_comments::Column::Status.eq(CommentStatus::Approved.as_str()))
+            .one(db)
+            .await?
+            .is_none()
+    {
+        return Err(CommentError::ParentNotFound);
     }
 
+    let status = comment_moderation::initial_status(db, &post, created_by).await?;
     let now = Utc::now();
     let comment = post_comments::ActiveModel {
         id: Set(Uuid::new_v4()),
         post_id: Set(post_id),
         text: Set(text),
         created_by: Set(created_by),
-        parent_comment_id: Set(None),
+        parent_comment_id: Set(parent_comment_id),
+        version: Set(1),
+        status: Set(status.as_str().to_string()),
+        reaction_count: Set(0),
         created_at: Set(Some(now.into())),
         updated_at: Set(Some(now.into())),
         deleted_at: Set(None),
@@ -71,39 +150,174 @@ pub async fn create_comment(
     .insert(db)
     .await?;
 
-    let users_by_id = load_comment_user_map(db, std::slice::from_ref(&comment)).await?;
-    Ok(hydrate_comment(comment, &users_by_id))
+    hydrate_one(db, comment, created_by).await
+}
+
+/// Not deleted and approved, as a condition on `post_comments`.
+fn is_visible() -> Condition {
+    Condition::all()
+        .add(post_comments::Column::DeletedAt.is_null())
+        .add(post_comments::Column::Status.eq(CommentStatus::Approved.as_str()))
+}
+
+/// Whether any reply below the comment, at any depth, is still visible.
+fn has_visible_reply() -> SimpleExpr {
+    Expr::cust(
+        "EXISTS (WITH RECURSIVE replies AS (\
+         SELECT r.id, r.deleted_at, r.status FROM post_comments r \
+         WHERE r.parent_comment_id = post_comments.id \
+         UNION ALL SELECT c.id, c.deleted_at, c.status FROM post_comments c \
+         JOIN replies ON c.parent_comment_id = replies.id) \
+         SELECT 1 FROM replies WHERE replies.deleted_at IS NULL AND replies.status = 'approved')",
+    )
+}
+
+/// Every reply below `root_ids`, deleted ones included, fetched one level at a time.
+async fn load_replies(
+    db: &DatabaseConnection,
+    root_ids: Vec<Uuid>,
+) -> Result<Vec<post_comments::Model>, DbErr> {
+    let mut replies = Vec::new();
+    let mut parent_ids = root_ids;
+    while !parent_ids.is_empty() {
+        let level = post_comments::Entity::find()
+            .filter(post_comments::Column::ParentCommentId.is_in(parent_ids))
+            .order_by_asc(post_comments::Column::CreatedAt)
+            .order_by_asc(post_comments::Column::Id)
+            .all(db)
+            .await?;
+        parent_ids = level.iter().map(|reply| reply.id).collect();
+        replies.extend(level);
+    }
+    Ok(replies)
+}
+
+/// Builds the thread under `comment`. Deleted, hidden and unapproved comments are dropped, or
+/// kept as placeholders while they have visible replies.
+fn build_thread(
+    comment: post_comments::Model,
+    depth: u32,
+    children: &mut HashMap<Uuid, Vec<post_comments::Model>>,
+    context: &mut CommentContext,
+) -> Option<CommentResponse> {
+    let replies: Vec<CommentResponse> = children
+        .remove(&comment.id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|reply| build_thread(reply, depth + 1, children, context))
+        .collect();
+    let response = hydrate_comment(comment, context);
+    if response.is_visible() {
+        return Some(response.with_replies(depth, replies));
+    }
+    if replies.is_empty() {
+        return None;
+    }
+    Some(response.into_placeholder().with_replies(depth, replies))
 }
 
+/// Top-level threads with their nested replies in `sort` order; paging applies to top-level
+/// comments only, and cursors only to `CommentSort::Newest`. Without a `page` every thread is
+/// returned. Reactions left by the viewer are flagged; posts `access` may not read are
+/// reported as missing.
 pub async fn get_comments_by_post_id(
     db: &DatabaseConnection,
     post_id: Uuid,
-) -> Result<Vec<CommentResponse>, CommentError> {
-    if !post_exists(db, post_id).await? {
+    page: Option<&Page>,
+    sort: CommentSort,
+    access: &PostAccess<'_>,
+) -> Result<Paged<CommentResponse>, CommentError> {
+    if visibility::find_viewable(db, post_id, access)
+        .await?
+        .is_none()
+    {
         return Err(CommentError::PostNotFound);
     }
+    let viewer = access.viewer;
 
-    let comments = post_comments::Entity::find()
+    let query = post_comments::Entity::find()
         .filter(post_comments::Column::PostId.eq(post_id))
-        .filter(post_comments::Column::DeletedAt.is_null())
-        .order_by_desc(post_comments::Column::CreatedAt)
-        .all(db)
-        .await?;
+        .filter(post_comments::Column::ParentCommentId.is_null())
+        .filter(Condition::any().add(is_visible()).add(has_visible_reply()));
 
-    let users_by_id = load_comment_user_map(db, &comments).await?;
-    let responses = comments
-        .into_iter()
-        .map(|comment| hydrate_comment(comment, &users_by_id))
-        .collect();
-    Ok(responses)
+    let roots = match (page, sort) {
+        (Some(page), CommentSort::Newest) => {
+            pagination::fetch_page(
+                db,
+                query,
+                (post_comments::Column::CreatedAt, post_comments::Column::Id),
+                page,
+                |comment| {
+                    comment
+                        .created_at
+                        .map(|created_at| Cursor::new(created_at, comment.id))
+                },
+            )
+            .await?
+        }
+        (Some(page), CommentSort::Top) => {
+            let query = query
+                .order_by_desc(post_comments::Column::ReactionCount)
+                .order_by_desc(post_comments::Column::CreatedAt)
+                .order_by_desc(post_comments::Column::Id);
+            pagination::fetch_offset(db, query, page).await?
+        }
+        (None, sort) => {
+            let query = match sort {
+                CommentSort::Newest => query,
+                CommentSort::Top => query.order_by_desc(post_comments::Column::ReactionCount),
+            };
+            Paged {
+                items: query
+                    .order_by_desc(post_comments::Column::CreatedAt)
+                    .all(db)
+                    .await?,
+                total: None,
+                next_cursor: None,
+            }
+        }
+    };
+
+    let Paged {
+        items,
+        total,
+        next_cursor,
+    } = roots;
+    let replies = load_replies(db, items.iter().map(|root| root.id).collect()).await?;
+    let mut context = load_comment_context(db, &items, viewer).await?;
+    let reply_context = load_comment_context(db, &replies, viewer).await?;
+    context.users_by_id.extend(reply_context.users_by_id);
+    context.reactions.extend(reply_context.reactions);
+
+    let mut children: HashMap<Uuid, Vec<post_comments::Model>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.parent_comment_id {
+            children.entry(parent_id).or_default().push(reply);
+        }
+    }
+    if sort == CommentSort::Top {
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|reply| Reverse(reply.reaction_count));
+        }
+    }
+    Ok(Paged {
+        items: items
+            .into_iter()
+            .filter_map(|root| build_thread(root, 0, &mut children, &mut context))
+            .collect(),
+        total,
+        next_cursor,
+    })
 }
 
+/// Replaces the text when the comment is still at `expected_version` (any version when `None`).
 pub async fn update_comment(
     db: &DatabaseConnection,
     post_id: Uuid,
     comment_id: Uuid,
     text: String,
     user_id: Uuid,
+    expected_version: Option<i32>,
 ) -> Result<CommentResponse, CommentError> {
     let Some(comment) = post_comments::Entity::find_by_id(comment_id)
         .filter(post_comments::Column::PostId.eq(post_id))
@@ -117,14 +331,38 @@ pub async fn update_comment(
     if comment.created_by != user_id {
         return Err(CommentError::NotOwner);
     }
+    if let Some(expected) = expected_version
+        && expected != comment.version
+    {
+        return Err(CommentError::VersionMismatch {
+            current: comment.version,
+        });
+    }
 
+    let version = comment.version;
     let mut active = comment.into_active_model();
     active.text = Set(text);
     active.updated_at = Set(Some(Utc::now().into()));
-    let updated = active.update(db).await?;
+    active.version = Set(version + 1);
+    let updated = match post_comments::Entity::update(active)
+        .filter(post_comments::Column::Version.eq(version))
+        .exec(db)
+        .await
+    {
+        Ok(updated) => updated,
+        Err(DbErr::RecordNotUpdated) => {
+            let current = post_comments::Entity::find_by_id(comment_id)
+                .one(db)
+                .await?
+                .ok_or(CommentError::CommentNotFound)?;
+            return Err(CommentError::VersionMismatch {
+                current: current.version,
+            });
+        }
+        Err(err) => return Err(err.into()),
+    };
 
-    let users_by_id = load_comment_user_map(db, std::slice::from_ref(&updated)).await?;
-    Ok(hydrate_comment(updated, &users_by_id))
+    hydrate_one(db, updated, user_id).await
 }
 
 pub async fn delete_comment(
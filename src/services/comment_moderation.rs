@@ -0,0 +1,326 @@
+//! Comment moderation by the post's author and site admins: approving, hiding and removing
+//! comments, locking a post's comments and requiring approval for first-time commenters.
+//! Every action is written to `comment_moderation_log`.
+
+use crate::entities::{comment_moderation_log, post_comments, posts};
+use crate::models::comment::CommentResponse;
+use crate::models::comment_moderation::{CommentSettingsResponse, ModerationLogEntry};
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::comment;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, JoinType, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Whether a comment is shown. Deletion is tracked separately in `deleted_at`.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentStatus {
+    Approved,
+    /// Waiting for the post author to approve a first-time commenter.
+    Pending,
+    /// Hidden by a moderator; can be approved again.
+    Hidden,
+}
+
+impl CommentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::Pending => "pending",
+            Self::Hidden => "hidden",
+        }
+    }
+
+    /// Unknown values are treated as hidden so a bad row is never shown.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "approved" => Self::Approved,
+            "pending" => Self::Pending,
+            _ => Self::Hidden,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    Approve,
+    Hide,
+    /// Deletes the comment; it stays as a placeholder while it has replies.
+    Remove,
+}
+
+impl ModerationAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Hide => "hide",
+            Self::Remove => "remove",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ModerationError {
+    Db(DbErr),
+    PostNotFound,
+    CommentNotFound,
+    NotModerator,
+}
+
+impl From<DbErr> for ModerationError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+/// The user acting as moderator; admins may moderate any post.
+#[derive(Clone, Copy)]
+pub struct Moderator {
+    pub id: Uuid,
+    pub is_admin: bool,
+}
+
+/// The live post, provided `moderator` may moderate its comments.
+async fn moderated_post(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    moderator: Moderator,
+) -> Result<posts::Model, ModerationError> {
+    let post = posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or(ModerationError::PostNotFound)?;
+    if post.created_by != moderator.id && !moderator.is_admin {
+        return Err(ModerationError::NotModerator);
+    }
+    Ok(post)
+}
+
+async fn log<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    comment_id: Option<Uuid>,
+    moderator_id: Uuid,
+    action: &str,
+    reason: Option<String>,
+) -> Result<(), DbErr> {
+    comment_moderation_log::ActiveModel {
+        post_id: Set(post_id),
+        comment_id: Set(comment_id),
+        moderator_id: Set(moderator_id),
+        action: Set(action.to_string()),
+        reason: Set(reason),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// Status for a new comment by `commenter_id` on `post`: pending only when the post requires
+/// approval and the commenter has no approved comment on any of the author's posts yet.
+pub(crate) async fn initial_status(
+    db: &DatabaseConnection,
+    post: &posts::Model,
+    commenter_id: Uuid,
+) -> Result<CommentStatus, DbErr> {
+    if !post.comment_approval_required || commenter_id == post.created_by {
+        return Ok(CommentStatus::Approved);
+    }
+    let approved_before = post_comments::Entity::find()
+        .join(JoinType::InnerJoin, post_comments::Relation::Posts.def())
+        .filter(posts::Column::CreatedBy.eq(post.created_by))
+        .filter(post_comments::Column::CreatedBy.eq(commenter_id))
+        .filter(post_comments::Column::Status.eq(CommentStatus::Approved.as_str()))
+        .filter(post_comments::Column::DeletedAt.is_null())
+        .count(db)
+        .await?
+        > 0;
+    Ok(if approved_before {
+        CommentStatus::Approved
+    } else {
+        CommentStatus::Pending
+    })
+}
+
+pub async fn moderate_comment(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    comment_id: Uuid,
+    moderator: Moderator,
+    action: ModerationAction,
+    reason: Option<String>,
+) -> Result<CommentResponse, ModerationError> {
+    moderated_post(db, post_id, moderator).await?;
+    let comment = post_comments::Entity::find_by_id(comment_id)
+        .filter(post_comments::Column::PostId.eq(post_id))
+        .filter(post_comments::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or(ModerationError::CommentNotFound)?;
+
+    let now = Utc::now();
+    let mut active = comment.into_active_model();
+    match action {
+        ModerationAction::Approve => {
+            active.status = Set(CommentStatus::Approved.as_str().to_string())
+        }
+        ModerationAction::Hide => active.status = Set(CommentStatus::Hidden.as_str().to_string()),
+        ModerationAction::Remove => active.deleted_at = Set(Some(now.into())),
+    }
+    active.updated_at = Set(Some(now.into()));
+
+    let txn = db.begin().await?;
+    let comment = active.update(&txn).await?;
+    log(
+        &txn,
+        post_id,
+        Some(comment_id),
+        moderator.id,
+        action.as_str(),
+        reason,
+    )
+    .await?;
+    txn.commit().await?;
+
+    comment::hydrate_comments(db, vec![comment], Some(moderator.id))
+        .await?
+        .pop()
+        .ok_or(ModerationError::CommentNotFound)
+}
+
+/// Changes whether comments are locked and whether first-time commenters need approval.
+/// Only actual changes are logged.
+pub async fn update_settings(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    moderator: Moderator,
+    comments_locked: Option<bool>,
+    comment_approval_required: Option<bool>,
+) -> Result<CommentSettingsResponse, ModerationError> {
+    let post = moderated_post(db, post_id, moderator).await?;
+    let mut actions = Vec::new();
+    if let Some(locked) = comments_locked.filter(|&value| value != post.comments_locked) {
+        actions.push(if locked { "lock" } else { "unlock" });
+    }
+    if let Some(required) =
+        comment_approval_required.filter(|&value| value != post.comment_approval_required)
+    {
+        actions.push(if required {
+            "require_approval"
+        } else {
+            "stop_requiring_approval"
+        });
+    }
+    if actions.is_empty() {
+        return Ok(CommentSettingsResponse::from_entity(&post));
+    }
+
+    let mut active = post.into_active_model();
+    if let Some(locked) = comments_locked {
+        active.comments_locked = Set(locked);
+    }
+    if let Some(required) = comment_approval_required {
+        active.comment_approval_required = Set(required);
+    }
+    let txn = db.begin().await?;
+    let post = active.update(&txn).await?;
+    for action in actions {
+        log(&txn, post_id, None, moderator.id, action, None).await?;
+    }
+    txn.commit().await?;
+    Ok(CommentSettingsResponse::from_entity(&post))
+}
+
+pub async fn get_settings(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    moderator: Moderator,
+) -> Result<CommentSettingsResponse, ModerationError> {
+    let post = moderated_post(db, post_id, moderator).await?;
+    Ok(CommentSettingsResponse::from_entity(&post))
+}
+
+/// Comments awaiting approval, newest first: on the moderator's own posts, or on every post
+/// for admins. `post_id` narrows the queue to one post.
+pub async fn moderation_queue(
+    db: &DatabaseConnection,
+    moderator: Moderator,
+    post_id: Option<Uuid>,
+    page: &Page,
+) -> Result<Paged<CommentResponse>, ModerationError> {
+    let mut query = post_comments::Entity::find()
+        .join(JoinType::InnerJoin, post_comments::Relation::Posts.def())
+        .filter(post_comments::Column::Status.eq(CommentStatus::Pending.as_str()))
+        .filter(post_comments::Column::DeletedAt.is_null())
+        .filter(posts::Column::DeletedAt.is_null());
+    if !moderator.is_admin {
+        query = query.filter(posts::Column::CreatedBy.eq(moderator.id));
+    }
+    if let Some(post_id) = post_id {
+        query = query.filter(post_comments::Column::PostId.eq(post_id));
+    }
+
+    let Paged {
+        items,
+        total,
+        next_cursor,
+    } = pagination::fetch_page(
+        db,
+        query,
+        (post_comments::Column::CreatedAt, post_comments::Column::Id),
+        page,
+        |comment| {
+            comment
+                .created_at
+                .map(|created_at| Cursor::new(created_at, comment.id))
+        },
+    )
+    .await?;
+    Ok(Paged {
+        items: comment::hydrate_comments(db, items, Some(moderator.id)).await?,
+        total,
+        next_cursor,
+    })
+}
+
+/// Moderation history of a post, newest first.
+pub async fn moderation_log(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    moderator: Moderator,
+    page: &Page,
+) -> Result<Paged<ModerationLogEntry>, ModerationError> {
+    moderated_post(db, post_id, moderator).await?;
+    let query = comment_moderation_log::Entity::find()
+        .filter(comment_moderation_log::Column::PostId.eq(post_id))
+        .order_by_desc(comment_moderation_log::Column::Id);
+    Ok(pagination::fetch_offset(db, query, page)
+        .await?
+        .map(ModerationLogEntry::from_entity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_status_is_hidden() {
+        for status in [
+            CommentStatus::Approved,
+            CommentStatus::Pending,
+            CommentStatus::Hidden,
+        ] {
+            assert_eq!(CommentStatus::parse(status.as_str()), status);
+        }
+        assert_eq!(CommentStatus::parse("spam"), CommentStatus::Hidden);
+    }
+}
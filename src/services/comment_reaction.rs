@@ -0,0 +1,239 @@
+use crate::entities::{comment_reactions, post_comments};
+use crate::models::comment::{CommentReactions, ReactionCount};
+use crate::services::comment_moderation::CommentStatus;
+use crate::services::counter;
+use crate::services::visibility::{self, PostAccess};
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait, TryInsertResult, sea_query::OnConflict,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The fixed set of reactions a comment accepts; `Like` doubles as a plain comment like.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Reaction {
+    Like,
+    Love,
+    Laugh,
+    Surprised,
+    Sad,
+}
+
+impl Reaction {
+    pub const ALL: [Reaction; 5] = [
+        Self::Like,
+        Self::Love,
+        Self::Laugh,
+        Self::Surprised,
+        Self::Sad,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Like => "like",
+            Self::Love => "love",
+            Self::Laugh => "laugh",
+            Self::Surprised => "surprised",
+            Self::Sad => "sad",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reaction| reaction.as_str() == value)
+    }
+}
+
+#[derive(Debug)]
+pub enum CommentReactionError {
+    Db(DbErr),
+    CommentNotFound,
+}
+
+impl From<DbErr> for CommentReactionError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+/// Only visible comments on posts `access` may read can be reacted to.
+async fn visible_comment_exists(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    comment_id: Uuid,
+    access: &PostAccess<'_>,
+) -> Result<bool, DbErr> {
+    if visibility::find_viewable(db, post_id, access)
+        .await?
+        .is_none()
+    {
+        return Ok(false);
+    }
+    Ok(post_comments::Entity::find_by_id(comment_id)
+        .filter(post_comments::Column::PostId.eq(post_id))
+        .filter(post_comments::Column::DeletedAt.is_null())
+        .filter(post_comments::Column::Status.eq(CommentStatus::Approved.as_str()))
+        .count(db)
+        .await?
+        > 0)
+}
+
+/// Idempotent; the count only changes when the reaction is new.
+pub async fn add_reaction(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    comment_id: Uuid,
+    user_id: Uuid,
+    reaction: Reaction,
+    share_token: Option<&str>,
+) -> Result<CommentReactions, CommentReactionError> {
+    let access = PostAccess::new(Some(user_id), share_token);
+    if !visible_comment_exists(db, post_id, comment_id, &access).await? {
+        return Err(CommentReactionError::CommentNotFound);
+    }
+
+    let txn = db.begin().await?;
+    let inserted = comment_reactions::Entity::insert(comment_reactions::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        comment_id: Set(comment_id),
+        user_id: Set(user_id),
+        reaction: Set(reaction.as_str().to_string()),
+        created_at: Set(Utc::now().into()),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            comment_reactions::Column::CommentId,
+            comment_reactions::Column::UserId,
+            comment_reactions::Column::Reaction,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .do_nothing()
+    .exec(&txn)
+    .await?;
+    if matches!(inserted, TryInsertResult::Inserted(_)) {
+        counter::adjust_comment_reactions(&txn, comment_id, 1).await?;
+    }
+    txn.commit().await?;
+
+    reactions_of(db, comment_id, user_id).await
+}
+
+/// Idempotent; removing a reaction the user never left is not an error.
+pub async fn remove_reaction(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    comment_id: Uuid,
+    user_id: Uuid,
+    reaction: Reaction,
+    share_token: Option<&str>,
+) -> Result<CommentReactions, CommentReactionError> {
+    let access = PostAccess::new(Some(user_id), share_token);
+    if !visible_comment_exists(db, post_id, comment_id, &access).await? {
+        return Err(CommentReactionError::CommentNotFound);
+    }
+
+    let txn = db.begin().await?;
+    let result = comment_reactions::Entity::delete_many()
+        .filter(comment_reactions::Column::CommentId.eq(comment_id))
+        .filter(comment_reactions::Column::UserId.eq(user_id))
+        .filter(comment_reactions::Column::Reaction.eq(reaction.as_str()))
+        .exec(&txn)
+        .await?;
+    if result.rows_affected > 0 {
+        counter::adjust_comment_reactions(&txn, comment_id, -(result.rows_affected as i64)).await?;
+    }
+    txn.commit().await?;
+
+    reactions_of(db, comment_id, user_id).await
+}
+
+async fn reactions_of(
+    db: &DatabaseConnection,
+    comment_id: Uuid,
+    viewer: Uuid,
+) -> Result<CommentReactions, CommentReactionError> {
+    Ok(load_reactions(db, &[comment_id], Some(viewer))
+        .await?
+        .remove(&comment_id)
+        .unwrap_or_default())
+}
+
+/// Per-comment reaction counts, with the reactions `viewer` left flagged. Comments without
+/// reactions are missing from the map.
+pub(crate) async fn load_reactions<C: ConnectionTrait>(
+    conn: &C,
+    comment_ids: &[Uuid],
+    viewer: Option<Uuid>,
+) -> Result<HashMap<Uuid, CommentReactions>, DbErr> {
+    if comment_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let counts: Vec<(Uuid, String, i64)> = comment_reactions::Entity::find()
+        .select_only()
+        .column(comment_reactions::Column::CommentId)
+        .column(comment_reactions::Column::Reaction)
+        .column_as(comment_reactions::Column::Id.count(), "count")
+        .filter(comment_reactions::Column::CommentId.is_in(comment_ids.to_vec()))
+        .group_by(comment_reactions::Column::CommentId)
+        .group_by(comment_reactions::Column::Reaction)
+        .into_tuple()
+        .all(conn)
+        .await?;
+    let mine: Vec<(Uuid, String)> = match viewer {
+        Some(viewer) => {
+            comment_reactions::Entity::find()
+                .select_only()
+                .column(comment_reactions::Column::CommentId)
+                .column(comment_reactions::Column::Reaction)
+                .filter(comment_reactions::Column::CommentId.is_in(comment_ids.to_vec()))
+                .filter(comment_reactions::Column::UserId.eq(viewer))
+                .into_tuple()
+                .all(conn)
+                .await?
+        }
+        None => Vec::new(),
+    };
+
+    let mut by_comment: HashMap<Uuid, CommentReactions> = HashMap::new();
+    for (comment_id, reaction, count) in counts {
+        let Some(reaction) = Reaction::parse(&reaction) else {
+            continue;
+        };
+        let reacted = mine
+            .iter()
+            .any(|(id, mine)| *id == comment_id && mine == reaction.as_str());
+        by_comment
+            .entry(comment_id)
+            .or_default()
+            .0
+            .push(ReactionCount {
+                reaction,
+                count,
+                reacted,
+            });
+    }
+    for reactions in by_comment.values_mut() {
+        reactions.0.sort_by_key(|count| count.reaction);
+    }
+    Ok(by_comment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reaction;
+
+    #[test]
+    fn reactions_round_trip_and_reject_unknown_values() {
+        for reaction in Reaction::ALL {
+            assert_eq!(Reaction::parse(reaction.as_str()), Some(reaction));
+        }
+        assert_eq!(Reaction::parse("angry"), None);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::entities::{post_comments, posts, users};
+use crate::models::report::{CounterDrift, CounterReconcileReport};
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, Statement, TransactionTrait, prelude::Expr,
+};
+use uuid::Uuid;
+
+#[derive(Clone, Copy)]
+pub enum PostCounter {
+    Views,
+    Likes,
+    Bookmarks,
+}
+
+impl PostCounter {
+    fn column(self) -> posts::Column {
+        match self {
+            Self::Views => posts::Column::ViewCount,
+            Self::Likes => posts::Column::LikeCount,
+            Self::Bookmarks => posts::Column::BookmarkCount,
+        }
+    }
+
+    fn column_name(self) -> &'static str {
+        match self {
+            Self::Views => "view_count",
+            Self::Likes => "like_count",
+            Self::Bookmarks => "bookmark_count",
+        }
+    }
+}
+
+/// Adds `delta` to a post counter, never letting it drop below zero. Call it in the same
+/// transaction that inserts or deletes the source row.
+pub async fn adjust_post<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    counter: PostCounter,
+    delta: i64,
+) -> Result<(), DbErr> {
+    if delta == 0 {
+        return Ok(());
+    }
+    posts::Entity::update_many()
+        .col_expr(
+            counter.column(),
+            Expr::cust_with_values(
+                format!("GREATEST(COALESCE({}, 0) + $1, 0)", counter.column_name()),
+                [delta],
+            ),
+        )
+        .filter(posts::Column::Id.eq(post_id))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Adjusts `following_count` of the follower and `followers_count` of the followed user.
+pub async fn adjust_follow<C: ConnectionTrait>(
+    conn: &C,
+    follower_id: Uuid,
+    following_id: Uuid,
+    delta: i64,
+) -> Result<(), DbErr> {
+    if delta == 0 {
+        return Ok(());
+    }
+    for (user_id, column, name) in [
+        (
+            follower_id,
+            users::Column::FollowingCount,
+            "following_count",
+        ),
+        (
+            following_id,
+            users::Column::FollowersCount,
+            "followers_count",
+        ),
+    ] {
+        users::Entity::update_many()
+            .col_expr(
+                column,
+                Expr::cust_with_values(format!("GREATEST(COALESCE({name}, 0) + $1, 0)"), [delta]),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Adds `delta` to a comment's `reaction_count`, never below zero.
+pub async fn adjust_comment_reactions<C: ConnectionTrait>(
+    conn: &C,
+    comment_id: Uuid,
+    delta: i64,
+) -> Result<(), DbErr> {
+    if delta == 0 {
+        return Ok(());
+    }
+    post_comments::Entity::update_many()
+        .col_expr(
+            post_comments::Column::ReactionCount,
+            Expr::cust_with_values("GREATEST(COALESCE(reaction_count, 0) + $1, 0)", [delta]),
+        )
+        .filter(post_comments::Column::Id.eq(comment_id))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// A counter column and the correlated subquery (over alias `t`) that defines its true value.
+struct CounterSource {
+    table: &'static str,
+    column: &'static str,
+    actual: &'static str,
+}
+
+const SOURCES: [CounterSource; 6] = [
+    CounterSource {
+        table: "posts",
+        column: "view_count",
+        actual: "SELECT COUNT(*) FROM post_views v WHERE v.post_id = t.id \
+            AND v.deleted_at IS NULL AND v.is_bot = false AND v.is_duplicate = false",
+    },
+    CounterSource {
+        table: "posts",
+        column: "like_count",
+        actual: "SELECT COUNT(*) FROM post_likes l WHERE l.post_id = t.id AND l.deleted_at IS NULL",
+    },
+    CounterSource {
+        table: "posts",
+        column: "bookmark_count",
+        actual: "SELECT COUNT(*) FROM post_bookmarks b WHERE b.post_id = t.id",
+    },
+    CounterSource {
+        table: "post_comments",
+        column: "reaction_count",
+        actual: "SELECT COUNT(*) FROM comment_reactions r WHERE r.comment_id = t.id",
+    },
+    CounterSource {
+        table: "users",
+        column: "followers_count",
+        actual: "SELECT COUNT(*) FROM user_follows f \
+            WHERE f.following_id = t.id AND f.deleted_at IS NULL",
+    },
+    CounterSource {
+        table: "users",
+        column: "following_count",
+        actual: "SELECT COUNT(*) FROM user_follows f \
+            WHERE f.follower_id = t.id AND f.deleted_at IS NULL",
+    },
+];
+
+#[derive(FromQueryResult)]
+struct DriftRow {
+    rows_corrected: i64,
+    total_drift: i64,
+}
+
+/// Rewrites every counter that disagrees with its source table and reports how far off each
+/// one was, repairing drift from writes that bypassed `adjust_*`. Runs in a single transaction.
+pub async fn reconcile(db: &DatabaseConnection) -> Result<CounterReconcileReport, DbErr> {
+    let txn = db.begin().await?;
+    let mut counters = Vec::with_capacity(SOURCES.len());
+    for source in &SOURCES {
+        let CounterSource {
+            table,
+            column,
+            actual,
+        } = source;
+        let row = DriftRow::find_by_statement(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                "WITH actual AS ( \
+                    SELECT t.id, t.{column} AS old_value, ({actual})::bigint AS value FROM {table} t \
+                 ), fixed AS ( \
+                    UPDATE {table} t SET {column} = a.value FROM actual a \
+                    WHERE t.id = a.id AND t.{column} IS DISTINCT FROM a.value \
+                    RETURNING ABS(a.value - COALESCE(a.old_value, 0)) AS drift \
+                 ) \
+                 SELECT COUNT(*)::bigint AS rows_corrected, \
+                 COALESCE(SUM(drift), 0)::bigint AS total_drift FROM fixed"
+            ),
+        ))
+        .one(&txn)
+        .await?;
+        let (rows_corrected, total_drift) = row
+            .map(|row| (row.rows_corrected, row.total_drift))
+            .unwrap_or_default();
+        counters.push(CounterDrift {
+            counter: format!("{table}.{column}"),
+            rows_corrected,
+            total_drift,
+        });
+    }
+    txn.commit().await?;
+
+    let total_rows_corrected = counters.iter().map(|c| c.rows_corrected).sum();
+    if total_rows_corrected > 0 {
+        tracing::warn!(
+            "Counter reconciliation corrected {} rows",
+            total_rows_corrected
+        );
+    }
+    Ok(CounterReconcileReport {
+        counters,
+        total_rows_corrected,
+        reconciled_at: Utc::now(),
+    })
+}
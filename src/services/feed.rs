@@ -0,0 +1,304 @@
+use crate::config::SiteConfig;
+use crate::models::post::Post;
+use crate::services::markdown;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const SUMMARY_CHARS: usize = 280;
+
+#[derive(Clone, Copy)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Rss => "application/rss+xml; charset=utf-8",
+            Self::Atom => "application/atom+xml; charset=utf-8",
+            Self::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Rss => "rss",
+            Self::Atom => "atom",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// What a feed is about: the whole site, one author or one tag.
+pub struct FeedChannel {
+    pub title: String,
+    pub description: String,
+    /// Site page the feed mirrors, relative to the site base URL.
+    pub home_path: String,
+    /// Public path of the feed itself, relative to the site base URL.
+    pub feed_path: String,
+}
+
+struct FeedItem<'a> {
+    post: &'a Post,
+    url: String,
+    author: Option<&'a str>,
+    published: DateTime<Utc>,
+    updated: DateTime<Utc>,
+}
+
+pub(crate) fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn published_at(post: &Post) -> DateTime<Utc> {
+    post.published_at
+        .or(post.created_at)
+        .unwrap_or_else(Utc::now)
+}
+
+fn updated_at(post: &Post) -> DateTime<Utc> {
+    post.updated_at
+        .unwrap_or_else(|| published_at(post))
+        .max(published_at(post))
+}
+
+pub fn post_url(site: &SiteConfig, post: &Post) -> String {
+    let username = post
+        .user
+        .as_ref()
+        .and_then(|user| user.username.as_deref())
+        .unwrap_or_default();
+    site.url(&format!("/{}/{}", username, post.slug))
+}
+
+fn items<'a>(site: &SiteConfig, posts: &'a [Post]) -> Vec<FeedItem<'a>> {
+    posts
+        .iter()
+        .map(|post| FeedItem {
+            post,
+            url: post_url(site, post),
+            author: post.user.as_ref().and_then(|user| user.username.as_deref()),
+            published: published_at(post),
+            updated: updated_at(post),
+        })
+        .collect()
+}
+
+fn summary(post: &Post) -> String {
+    let body = post.body.as_deref().unwrap_or_default().trim();
+    if body.chars().count() > SUMMARY_CHARS {
+        format!(
+            "{}...",
+            body.chars().take(SUMMARY_CHARS).collect::<String>()
+        )
+    } else {
+        body.to_string()
+    }
+}
+
+/// Uses the render cached by `post_revision::rendered_bodies` when the post carries one.
+fn content_html(post: &Post) -> String {
+    match &post.rendered {
+        Some(rendered) => rendered.html.clone(),
+        None => markdown::render(post.body.as_deref().unwrap_or_default()).html,
+    }
+}
+
+/// Newest change across the feed's posts, used for `Last-Modified` and the feed's own
+/// updated timestamp. Truncated to whole seconds to match HTTP dates.
+pub fn last_modified(posts: &[Post]) -> DateTime<Utc> {
+    let latest = posts
+        .iter()
+        .map(updated_at)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH);
+    DateTime::from_timestamp(latest.timestamp(), 0).unwrap_or(latest)
+}
+
+/// Strong validator over everything that affects the feed body, computed without rendering it.
+pub fn etag(format: FeedFormat, channel: &FeedChannel, posts: &[Post]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format.extension());
+    hasher.update(&channel.feed_path);
+    hasher.update(markdown::RENDER_VERSION.to_le_bytes());
+    for post in posts {
+        hasher.update(post.id.as_bytes());
+        hasher.update(updated_at(post).timestamp().to_le_bytes());
+    }
+    let digest = hasher.finalize();
+    format!("\"{}\"", hex_prefix(&digest, 16))
+}
+
+fn hex_prefix(bytes: &[u8], len: usize) -> String {
+    bytes
+        .iter()
+        .take(len)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn render(
+    format: FeedFormat,
+    site: &SiteConfig,
+    channel: &FeedChannel,
+    posts: &[Post],
+) -> String {
+    match format {
+        FeedFormat::Rss => render_rss(site, channel, posts),
+        FeedFormat::Atom => render_atom(site, channel, posts),
+        FeedFormat::Json => render_json(site, channel, posts),
+    }
+}
+
+fn render_rss(site: &SiteConfig, channel: &FeedChannel, posts: &[Post]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>"#);
+    xml.push_str(&format!(
+        "<title>{}</title><link>{}</link><description>{}</description>",
+        xml_escape(&channel.title),
+        xml_escape(&site.url(&channel.home_path)),
+        xml_escape(&channel.description),
+    ));
+    xml.push_str(&format!(
+        r#"<atom:link href="{}" rel="self" type="application/rss+xml"/>"#,
+        xml_escape(&site.url(&channel.feed_path)),
+    ));
+    xml.push_str(&format!(
+        "<lastBuildDate>{}</lastBuildDate>",
+        last_modified(posts).to_rfc2822()
+    ));
+
+    for item in items(site, posts) {
+        xml.push_str("<item>");
+        xml.push_str(&format!(
+            r#"<title>{}</title><link>{}</link><guid isPermaLink="true">{}</guid><pubDate>{}</pubDate>"#,
+            xml_escape(&item.post.title),
+            xml_escape(&item.url),
+            xml_escape(&item.url),
+            item.published.to_rfc2822(),
+        ));
+        if let Some(author) = item.author {
+            xml.push_str(&format!("<dc:creator>{}</dc:creator>", xml_escape(author)));
+        }
+        for tag in &item.post.tags {
+            xml.push_str(&format!("<category>{}</category>", xml_escape(&tag.name)));
+        }
+        xml.push_str(&format!(
+            "<description>{}</description><content:encoded>{}</content:encoded>",
+            xml_escape(&summary(item.post)),
+            xml_escape(&content_html(item.post)),
+        ));
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn render_atom(site: &SiteConfig, channel: &FeedChannel, posts: &[Post]) -> String {
+    let rfc3339 = |value: DateTime<Utc>| value.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    xml.push_str(&format!(
+        r#"<id>{}</id><title>{}</title><subtitle>{}</subtitle><updated>{}</updated><link rel="self" type="application/atom+xml" href="{}"/><link rel="alternate" type="text/html" href="{}"/>"#,
+        xml_escape(&site.url(&channel.feed_path)),
+        xml_escape(&channel.title),
+        xml_escape(&channel.description),
+        rfc3339(last_modified(posts)),
+        xml_escape(&site.url(&channel.feed_path)),
+        xml_escape(&site.url(&channel.home_path)),
+    ));
+
+    for item in items(site, posts) {
+        xml.push_str("<entry>");
+        xml.push_str(&format!(
+            r#"<id>{}</id><title>{}</title><link rel="alternate" type="text/html" href="{}"/><published>{}</published><updated>{}</updated>"#,
+            xml_escape(&item.url),
+            xml_escape(&item.post.title),
+            xml_escape(&item.url),
+            rfc3339(item.published),
+            rfc3339(item.updated),
+        ));
+        if let Some(author) = item.author {
+            xml.push_str(&format!(
+                "<author><name>{}</name><uri>{}</uri></author>",
+                xml_escape(author),
+                xml_escape(&site.url(&format!("/{}", author))),
+            ));
+        }
+        for tag in &item.post.tags {
+            xml.push_str(&format!(r#"<category term="{}"/>"#, xml_escape(&tag.name)));
+        }
+        xml.push_str(&format!(
+            r#"<summary>{}</summary><content type="html">{}</content>"#,
+            xml_escape(&summary(item.post)),
+            xml_escape(&content_html(item.post)),
+        ));
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn render_json(site: &SiteConfig, channel: &FeedChannel, posts: &[Post]) -> String {
+    let items: Vec<serde_json::Value> = items(site, posts)
+        .into_iter()
+        .map(|item| {
+            json!({
+                "id": item.url,
+                "url": item.url,
+                "title": item.post.title,
+                "summary": summary(item.post),
+                "content_html": content_html(item.post),
+                "image": item.post.photo_url,
+                "date_published": item.published.to_rfc3339_opts(SecondsFormat::Secs, true),
+                "date_modified": item.updated.to_rfc3339_opts(SecondsFormat::Secs, true),
+                "authors": item.author.map(|author| vec![json!({
+                    "name": author,
+                    "url": site.url(&format!("/{}", author)),
+                })]),
+                "tags": item.post.tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": channel.title,
+        "description": channel.description,
+        "home_page_url": site.url(&channel.home_path),
+        "feed_url": site.url(&channel.feed_path),
+        "items": items,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::xml_escape;
+
+    #[test]
+    fn xml_escape_replaces_markup_characters() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+}
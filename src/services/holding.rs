@@ -1,10 +1,14 @@
-use crate::entities::{holding_types, holdings};
+use crate::entities::{holding_audits, holding_types, holdings};
 use crate::models::holding::*;
+use crate::services::holding_audit::{self, AuditAction, HoldingSnapshot};
+use crate::services::holding_projection::{self, MonthlyPoint, ProjectionBucket, ProjectionParams};
 use chrono::{DateTime, Datelike, Utc};
-use sea_orm::prelude::Decimal;
+use rand::{SeedableRng, rngs::StdRng};
+use sea_orm::prelude::{Decimal, Expr};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
     FromQueryResult, IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, Set, Statement,
+    TransactionTrait,
 };
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
@@ -17,6 +21,8 @@ pub enum HoldingError {
     HoldingTypeNotFound,
     InvalidDecimal(&'static str),
     DuplicateSameMonth,
+    InsufficientHistory,
+    HistoryEntryNotFound,
 }
 
 impl From<DbErr> for HoldingError {
@@ -61,6 +67,29 @@ pub struct UpdateHoldingInput {
     pub year: Option<i32>,
 }
 
+pub struct ProjectionInput {
+    pub month: i32,
+    pub year: i32,
+    pub years: u32,
+    pub monthly_contribution: f64,
+    pub expected_returns: HashMap<String, f64>,
+    pub default_return: f64,
+    pub inflation: f64,
+    pub monte_carlo: Option<MonteCarloInput>,
+}
+
+pub struct MonteCarloInput {
+    pub simulations: u32,
+    pub seed: Option<u64>,
+}
+
+/// Identifies one position across the monthly copies of a holding.
+#[derive(Clone)]
+pub enum PositionKey {
+    Symbol(String),
+    NamePlatform { name: String, platform: String },
+}
+
 #[derive(Clone, Copy)]
 struct SummaryValues {
     invested: f64,
@@ -68,6 +97,14 @@ struct SummaryValues {
     count: i64,
 }
 
+#[derive(Clone, Copy)]
+struct TrendValues {
+    month: i32,
+    year: i32,
+    invested: f64,
+    current: f64,
+}
+
 #[derive(Clone)]
 struct BreakdownValues {
     name: String,
@@ -258,6 +295,7 @@ pub async fn create_holding(
     }
 
     let now = Utc::now().into();
+    let txn = db.begin().await?;
     let model = holdings::ActiveModel {
         user_id: Set(user_id),
         name: Set(input.name),
@@ -284,8 +322,18 @@ pub async fn create_holding(
         year: Set(input.year),
         ..Default::default()
     }
-    .insert(db)
+    .insert(&txn)
     .await?;
+    holding_audit::record(
+        &txn,
+        &model,
+        user_id,
+        AuditAction::Create,
+        None,
+        Some(&HoldingSnapshot::from(&model)),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(hydrate(db, model).await?)
 }
@@ -310,6 +358,7 @@ pub async fn update_holding(
         return Err(HoldingError::HoldingTypeNotFound);
     }
 
+    let before = HoldingSnapshot::from(&existing);
     let mut active = existing.into_active_model();
     if let Some(value) = input.name {
         active.name = Set(value);
@@ -361,7 +410,18 @@ pub async fn update_holding(
     }
     active.updated_at = Set(Utc::now().into());
 
-    let updated = active.update(db).await?;
+    let txn = db.begin().await?;
+    let updated = active.update(&txn).await?;
+    holding_audit::record(
+        &txn,
+        &updated,
+        user_id,
+        AuditAction::Update,
+        Some(&before),
+        Some(&HoldingSnapshot::from(&updated)),
+    )
+    .await?;
+    txn.commit().await?;
     Ok(hydrate(db, updated).await?)
 }
 
@@ -370,17 +430,137 @@ pub async fn delete_holding(
     id: i64,
     user_id: Uuid,
 ) -> Result<(), HoldingError> {
-    let result = holdings::Entity::delete_many()
-        .filter(holdings::Column::Id.eq(id))
+    let txn = db.begin().await?;
+    let Some(existing) = holdings::Entity::find_by_id(id)
         .filter(holdings::Column::UserId.eq(user_id))
-        .exec(db)
+        .one(&txn)
+        .await?
+    else {
+        return Err(HoldingError::NotFound);
+    };
+    holdings::Entity::delete_by_id(existing.id)
+        .exec(&txn)
         .await?;
-    if result.rows_affected == 0 {
+    holding_audit::record(
+        &txn,
+        &existing,
+        user_id,
+        AuditAction::Delete,
+        Some(&HoldingSnapshot::from(&existing)),
+        None,
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+pub async fn get_holding_history(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: Uuid,
+) -> Result<Vec<HoldingAuditResponse>, HoldingError> {
+    let entries = holding_audits::Entity::find()
+        .filter(holding_audits::Column::HoldingId.eq(id))
+        .filter(holding_audits::Column::UserId.eq(user_id))
+        .order_by_desc(holding_audits::Column::CreatedAt)
+        .order_by_desc(holding_audits::Column::Id)
+        .all(db)
+        .await?;
+    if entries.is_empty() {
         return Err(HoldingError::NotFound);
     }
+    Ok(entries.into_iter().map(Into::into).collect())
+}
+
+fn apply_snapshot(
+    active: &mut holdings::ActiveModel,
+    snapshot: HoldingSnapshot,
+) -> Result<(), HoldingError> {
+    active.name = Set(snapshot.name);
+    active.symbol = Set(snapshot.symbol);
+    active.platform = Set(snapshot.platform);
+    active.holding_type_id = Set(snapshot.holding_type_id);
+    active.currency = Set(snapshot.currency);
+    active.invested_amount = Set(parse_decimal(&snapshot.invested_amount, "invested_amount")?);
+    active.current_value = Set(parse_decimal(&snapshot.current_value, "current_value")?);
+    active.units = Set(parse_optional_decimal(snapshot.units, "units")?);
+    active.avg_buy_price = Set(parse_optional_decimal(
+        snapshot.avg_buy_price,
+        "avg_buy_price",
+    )?);
+    active.current_price = Set(parse_optional_decimal(
+        snapshot.current_price,
+        "current_price",
+    )?);
+    active.last_updated = Set(snapshot.last_updated);
+    active.notes = Set(snapshot.notes);
+    active.month = Set(snapshot.month);
+    active.year = Set(snapshot.year);
     Ok(())
 }
 
+/// Puts a holding back into the state captured by one of its history entries. A deleted
+/// holding is re-created under its original id.
+pub async fn restore_holding_version(
+    db: &DatabaseConnection,
+    id: i64,
+    audit_id: i64,
+    user_id: Uuid,
+) -> Result<HoldingResponse, HoldingError> {
+    let Some(snapshot) = holding_audits::Entity::find_by_id(audit_id)
+        .filter(holding_audits::Column::HoldingId.eq(id))
+        .filter(holding_audits::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .and_then(|entry| entry.snapshot)
+        .and_then(|raw| serde_json::from_str::<HoldingSnapshot>(&raw).ok())
+    else {
+        return Err(HoldingError::HistoryEntryNotFound);
+    };
+    if !holding_type_exists(db, snapshot.holding_type_id).await? {
+        return Err(HoldingError::HoldingTypeNotFound);
+    }
+
+    let txn = db.begin().await?;
+    let existing = holdings::Entity::find_by_id(id)
+        .filter(holdings::Column::UserId.eq(user_id))
+        .one(&txn)
+        .await?;
+    let before = existing.as_ref().map(HoldingSnapshot::from);
+    let now = Utc::now().into();
+    let restored = match existing {
+        Some(existing) => {
+            let mut active = existing.into_active_model();
+            apply_snapshot(&mut active, snapshot)?;
+            active.updated_at = Set(now);
+            active.update(&txn).await?
+        }
+        None => {
+            let mut active = holdings::ActiveModel {
+                id: Set(id),
+                user_id: Set(user_id),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            };
+            apply_snapshot(&mut active, snapshot)?;
+            active.insert(&txn).await?
+        }
+    };
+    holding_audit::record(
+        &txn,
+        &restored,
+        user_id,
+        AuditAction::Restore,
+        before.as_ref(),
+        Some(&HoldingSnapshot::from(&restored)),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(hydrate(db, restored).await?)
+}
+
 async fn summary_values(
     db: &DatabaseConnection,
     user_id: Uuid,
@@ -503,11 +683,11 @@ pub async fn summary(
     })
 }
 
-pub async fn trends(
+async fn trend_values(
     db: &DatabaseConnection,
     user_id: Uuid,
     years: Vec<i32>,
-) -> Result<Vec<HoldingTrendResponse>, HoldingError> {
+) -> Result<Vec<TrendValues>, DbErr> {
     #[derive(FromQueryResult)]
     struct Row {
         month: i32,
@@ -536,16 +716,33 @@ pub async fn trends(
     ))
     .all(db)
     .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| TrendValues {
+            month: row.month,
+            year: row.year,
+            invested: row.invested,
+            current: row.current_value,
+        })
+        .collect())
+}
+
+pub async fn trends(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    years: Vec<i32>,
+) -> Result<Vec<HoldingTrendResponse>, HoldingError> {
+    let rows = trend_values(db, user_id, years).await?;
     Ok(rows
         .into_iter()
         .map(|row| {
-            let profit_loss = row.current_value - row.invested;
+            let profit_loss = row.current - row.invested;
             HoldingTrendResponse {
                 date: format!("{:04}-{:02}", row.year, row.month),
                 invested: format_float(row.invested),
-                current: format_float(row.current_value),
+                current: format_float(row.current),
                 profit_loss: format_float(profit_loss),
-                profit_loss_percentage: format_float(calc_percent(row.invested, row.current_value)),
+                profit_loss_percentage: format_float(calc_percent(row.invested, row.current)),
             }
         })
         .collect())
@@ -737,6 +934,218 @@ pub async fn monthly_data(
     Ok(result)
 }
 
+pub async fn position_history(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    key: PositionKey,
+) -> Result<HoldingPositionHistoryResponse, HoldingError> {
+    let mut query = holdings::Entity::find().filter(holdings::Column::UserId.eq(user_id));
+    query = match &key {
+        PositionKey::Symbol(symbol) => query.filter(Expr::cust_with_values(
+            "UPPER(TRIM(symbol)) = UPPER(TRIM($1))",
+            [symbol.clone()],
+        )),
+        PositionKey::NamePlatform { name, platform } => query
+            .filter(holdings::Column::Name.eq(name.as_str()))
+            .filter(holdings::Column::Platform.eq(platform.as_str())),
+    };
+    let rows = query
+        .order_by_asc(holdings::Column::Year)
+        .order_by_asc(holdings::Column::Month)
+        .order_by_asc(holdings::Column::Id)
+        .all(db)
+        .await?;
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        return Err(HoldingError::NotFound);
+    };
+
+    // Rows for the same symbol on several platforms share a month, so each month is
+    // reported as the sum of its rows.
+    let history: Vec<HoldingPositionPoint> = rows
+        .chunk_by(|a, b| a.year == b.year && a.month == b.month)
+        .map(|month_rows| {
+            let row = &month_rows[0];
+            let units = month_rows.iter().map(|item| item.units).sum();
+            let current_price = if month_rows.len() == 1 {
+                row.current_price
+            } else {
+                None
+            };
+            HoldingPositionPoint::from_totals(
+                row.month,
+                row.year,
+                units,
+                current_price,
+                month_rows.iter().map(|item| item.invested_amount).sum(),
+                month_rows.iter().map(|item| item.current_value).sum(),
+                month_rows.len() as i64,
+            )
+        })
+        .collect();
+
+    let platform = match &key {
+        PositionKey::NamePlatform { platform, .. } => Some(platform.clone()),
+        PositionKey::Symbol(_) => {
+            let platforms: HashSet<&str> = rows.iter().map(|row| row.platform.as_str()).collect();
+            (platforms.len() == 1).then(|| last.platform.clone())
+        }
+    };
+
+    Ok(HoldingPositionHistoryResponse {
+        symbol: last.symbol.clone(),
+        name: last.name.clone(),
+        platform,
+        currency: last.currency.clone(),
+        first_seen: format!("{:04}-{:02}", first.year, first.month),
+        last_seen: format!("{:04}-{:02}", last.year, last.month),
+        months_held: history.len() as i64,
+        history,
+    })
+}
+
+fn expected_return(
+    expected_returns: &HashMap<String, f64>,
+    name: &str,
+    code: Option<&str>,
+    default_return: f64,
+) -> f64 {
+    expected_returns
+        .iter()
+        .find(|(key, _)| {
+            key.eq_ignore_ascii_case(name)
+                || code.is_some_and(|code| key.eq_ignore_ascii_case(code))
+        })
+        .map(|(_, value)| *value)
+        .unwrap_or(default_return)
+}
+
+pub async fn projection(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    input: ProjectionInput,
+) -> Result<HoldingProjectionResponse, HoldingError> {
+    let codes_by_name: HashMap<String, String> = holding_types::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|holding_type| (holding_type.name, holding_type.code))
+        .collect();
+    let buckets: Vec<ProjectionBucket> =
+        named_breakdown(db, user_id, Some(input.month), Some(input.year), true)
+            .await?
+            .into_iter()
+            .map(|item| ProjectionBucket {
+                annual_return: expected_return(
+                    &input.expected_returns,
+                    &item.name,
+                    codes_by_name.get(&item.name).map(String::as_str),
+                    input.default_return,
+                ),
+                name: item.name,
+                value: item.current,
+            })
+            .collect();
+    let buckets = if buckets.is_empty() {
+        vec![ProjectionBucket {
+            name: "Unallocated".to_string(),
+            value: 0.0,
+            annual_return: input.default_return,
+        }]
+    } else {
+        buckets
+    };
+
+    let params = ProjectionParams {
+        years: input.years,
+        monthly_contribution: input.monthly_contribution,
+        inflation: input.inflation,
+    };
+    let nominal = holding_projection::deterministic(&buckets, &params);
+
+    let (volatility, ranges) = match &input.monte_carlo {
+        Some(monte_carlo) => {
+            let history: Vec<MonthlyPoint> = trend_values(db, user_id, Vec::new())
+                .await?
+                .into_iter()
+                .map(|row| MonthlyPoint {
+                    month: row.month,
+                    year: row.year,
+                    invested: row.invested,
+                    current: row.current,
+                })
+                .collect();
+            let Some(volatility) = holding_projection::monthly_volatility(&history) else {
+                return Err(HoldingError::InsufficientHistory);
+            };
+            let mut rng = match monte_carlo.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            let ranges = holding_projection::monte_carlo(
+                &buckets,
+                &params,
+                volatility,
+                monte_carlo.simulations,
+                &mut rng,
+            );
+            (Some(volatility), Some(ranges))
+        }
+        None => (None, None),
+    };
+
+    let series = nominal
+        .iter()
+        .enumerate()
+        .map(|(offset, value)| {
+            let offset = offset as u32;
+            let factor = holding_projection::inflation_factor(input.inflation, offset);
+            let range = ranges.as_ref().map(|ranges| ranges[offset as usize]);
+            HoldingProjectionPoint {
+                year: input.year + offset as i32,
+                years_from_now: offset,
+                contributed: round2(input.monthly_contribution * 12.0 * offset as f64),
+                nominal_value: round2(*value),
+                real_value: round2(value / factor),
+                nominal_range: range.map(|range| HoldingProjectionRange {
+                    p10: round2(range.p10),
+                    p50: round2(range.p50),
+                    p90: round2(range.p90),
+                }),
+                real_range: range.map(|range| HoldingProjectionRange {
+                    p10: round2(range.p10 / factor),
+                    p50: round2(range.p50 / factor),
+                    p90: round2(range.p90 / factor),
+                }),
+            }
+        })
+        .collect();
+
+    Ok(HoldingProjectionResponse {
+        month: input.month,
+        year: input.year,
+        mode: if input.monte_carlo.is_some() {
+            "monteCarlo".to_string()
+        } else {
+            "deterministic".to_string()
+        },
+        starting_value: round2(nominal[0]),
+        monthly_contribution: input.monthly_contribution,
+        inflation: input.inflation,
+        // Annualised the usual way: monthly sigma * sqrt(12), in percent.
+        annual_volatility: volatility.map(|value| round2(value * 12f64.sqrt() * 100.0)),
+        simulations: input.monte_carlo.as_ref().map(|value| value.simulations),
+        buckets: buckets
+            .iter()
+            .map(|bucket| HoldingProjectionBucket {
+                name: bucket.name.clone(),
+                current_value: round2(bucket.value),
+                expected_return: bucket.annual_return,
+            })
+            .collect(),
+        series,
+    })
+}
+
 pub async fn duplicate_holdings(
     db: &DatabaseConnection,
     user_id: Uuid,
@@ -758,13 +1167,26 @@ pub async fn duplicate_holdings(
     if source.is_empty() {
         return Err(HoldingError::NotFound);
     }
+    let txn = db.begin().await?;
     if overwrite {
-        holdings::Entity::delete_many()
+        let replaced = holdings::Entity::find()
             .filter(holdings::Column::UserId.eq(user_id))
             .filter(holdings::Column::Month.eq(to_month))
             .filter(holdings::Column::Year.eq(to_year))
-            .exec(db)
+            .all(&txn)
+            .await?;
+        for item in replaced {
+            holdings::Entity::delete_by_id(item.id).exec(&txn).await?;
+            holding_audit::record(
+                &txn,
+                &item,
+                user_id,
+                AuditAction::Delete,
+                Some(&HoldingSnapshot::from(&item)),
+                None,
+            )
             .await?;
+        }
     }
     let mut out = Vec::with_capacity(source.len());
     for item in source {
@@ -788,7 +1210,16 @@ pub async fn duplicate_holdings(
             updated_at: Set(now),
             ..Default::default()
         }
-        .insert(db)
+        .insert(&txn)
+        .await?;
+        holding_audit::record(
+            &txn,
+            &created,
+            user_id,
+            AuditAction::Create,
+            None,
+            Some(&HoldingSnapshot::from(&created)),
+        )
         .await?;
         out.push(DuplicateResultItem {
             id: created.id.to_string(),
@@ -797,6 +1228,7 @@ pub async fn duplicate_holdings(
             year: created.year,
         });
     }
+    txn.commit().await?;
     Ok(out)
 }
 
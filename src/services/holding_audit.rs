@@ -0,0 +1,129 @@
+use crate::entities::{holding_audits, holdings};
+use chrono::{DateTime, FixedOffset, Utc};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+        }
+    }
+}
+
+/// The user-editable columns of a holding, as stored in `holding_audits.snapshot`.
+/// Decimals are kept as strings so the stored JSON round-trips exactly.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HoldingSnapshot {
+    pub name: String,
+    pub symbol: Option<String>,
+    pub platform: String,
+    pub holding_type_id: i16,
+    pub currency: String,
+    pub invested_amount: String,
+    pub current_value: String,
+    pub units: Option<String>,
+    pub avg_buy_price: Option<String>,
+    pub current_price: Option<String>,
+    pub last_updated: Option<DateTime<FixedOffset>>,
+    pub notes: Option<String>,
+    pub month: i32,
+    pub year: i32,
+}
+
+impl From<&holdings::Model> for HoldingSnapshot {
+    fn from(model: &holdings::Model) -> Self {
+        Self {
+            name: model.name.clone(),
+            symbol: model.symbol.clone(),
+            platform: model.platform.clone(),
+            holding_type_id: model.holding_type_id,
+            currency: model.currency.clone(),
+            invested_amount: model.invested_amount.normalize().to_string(),
+            current_value: model.current_value.normalize().to_string(),
+            units: model.units.map(|value| value.normalize().to_string()),
+            avg_buy_price: model
+                .avg_buy_price
+                .map(|value| value.normalize().to_string()),
+            current_price: model
+                .current_price
+                .map(|value| value.normalize().to_string()),
+            last_updated: model.last_updated,
+            notes: model.notes.clone(),
+            month: model.month,
+            year: model.year,
+        }
+    }
+}
+
+fn snapshot_fields(snapshot: Option<&HoldingSnapshot>) -> Map<String, Value> {
+    match snapshot.and_then(|snapshot| serde_json::to_value(snapshot).ok()) {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+/// Field-level `{ "field": { "before": .., "after": .. } }` for every field that differs.
+fn diff(before: Option<&HoldingSnapshot>, after: Option<&HoldingSnapshot>) -> Map<String, Value> {
+    let before = snapshot_fields(before);
+    let after = snapshot_fields(after);
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        if changes.contains_key(key) {
+            continue;
+        }
+        let old = before.get(key).cloned().unwrap_or(Value::Null);
+        let new = after.get(key).cloned().unwrap_or(Value::Null);
+        if old != new {
+            changes.insert(key.clone(), json!({ "before": old, "after": new }));
+        }
+    }
+    changes
+}
+
+/// Records one audit row. `snapshot` keeps the state after the action, or the last known
+/// state for deletes, so any entry can be restored later. Updates that change nothing are
+/// not recorded.
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    holding: &holdings::Model,
+    actor_id: Uuid,
+    action: AuditAction,
+    before: Option<&HoldingSnapshot>,
+    after: Option<&HoldingSnapshot>,
+) -> Result<(), DbErr> {
+    let changes = diff(before, after);
+    if action == AuditAction::Update && changes.is_empty() {
+        return Ok(());
+    }
+    let snapshot = after
+        .or(before)
+        .and_then(|snapshot| serde_json::to_string(snapshot).ok());
+
+    holding_audits::ActiveModel {
+        holding_id: Set(holding.id),
+        user_id: Set(holding.user_id),
+        actor_id: Set(actor_id),
+        action: Set(action.as_str().to_string()),
+        changes: Set(Some(Value::Object(changes).to_string())),
+        snapshot: Set(snapshot),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use rand::Rng;
+
+pub struct ProjectionBucket {
+    pub name: String,
+    pub value: f64,
+    /// Expected annual return in percent.
+    pub annual_return: f64,
+}
+
+pub struct ProjectionParams {
+    pub years: u32,
+    pub monthly_contribution: f64,
+    /// Expected annual inflation in percent.
+    pub inflation: f64,
+}
+
+#[derive(Clone, Copy)]
+pub struct Percentiles {
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+/// One month-end portfolio total per calendar month, as returned by `trends`.
+#[derive(Clone, Copy)]
+pub struct MonthlyPoint {
+    pub month: i32,
+    pub year: i32,
+    pub invested: f64,
+    pub current: f64,
+}
+
+fn monthly_rate(annual_percent: f64) -> f64 {
+    (1.0 + annual_percent / 100.0).powf(1.0 / 12.0) - 1.0
+}
+
+pub fn inflation_factor(inflation: f64, years: u32) -> f64 {
+    (1.0 + inflation / 100.0).powi(years as i32)
+}
+
+/// New money is split across buckets in proportion to their current value, or evenly when
+/// the portfolio is empty.
+fn contribution_weights(buckets: &[ProjectionBucket]) -> Vec<f64> {
+    let total: f64 = buckets.iter().map(|bucket| bucket.value.max(0.0)).sum();
+    if total > 0.0 {
+        buckets
+            .iter()
+            .map(|bucket| bucket.value.max(0.0) / total)
+            .collect()
+    } else {
+        vec![1.0 / buckets.len().max(1) as f64; buckets.len()]
+    }
+}
+
+/// Runs one path month by month and returns the portfolio value at the start and at the end
+/// of every projected year. `shock` is added to every bucket's monthly return.
+fn simulate_path(
+    buckets: &[ProjectionBucket],
+    params: &ProjectionParams,
+    mut shock: impl FnMut() -> f64,
+) -> Vec<f64> {
+    let rates: Vec<f64> = buckets
+        .iter()
+        .map(|bucket| monthly_rate(bucket.annual_return))
+        .collect();
+    let weights = contribution_weights(buckets);
+    let mut values: Vec<f64> = buckets.iter().map(|bucket| bucket.value).collect();
+
+    let mut path = Vec::with_capacity(params.years as usize + 1);
+    path.push(values.iter().sum());
+    for month in 1..=params.years * 12 {
+        let shock = shock();
+        for ((value, rate), weight) in values.iter_mut().zip(&rates).zip(&weights) {
+            *value =
+                (*value * (1.0 + rate + shock) + params.monthly_contribution * weight).max(0.0);
+        }
+        if month % 12 == 0 {
+            path.push(values.iter().sum());
+        }
+    }
+    path
+}
+
+pub fn deterministic(buckets: &[ProjectionBucket], params: &ProjectionParams) -> Vec<f64> {
+    simulate_path(buckets, params, || 0.0)
+}
+
+pub fn monte_carlo<R: Rng>(
+    buckets: &[ProjectionBucket],
+    params: &ProjectionParams,
+    monthly_volatility: f64,
+    simulations: u32,
+    rng: &mut R,
+) -> Vec<Percentiles> {
+    let paths: Vec<Vec<f64>> = (0..simulations)
+        .map(|_| {
+            simulate_path(buckets, params, || {
+                monthly_volatility * standard_normal(rng)
+            })
+        })
+        .collect();
+
+    (0..=params.years as usize)
+        .map(|year| {
+            let mut values: Vec<f64> = paths.iter().map(|path| path[year]).collect();
+            values.sort_by(f64::total_cmp);
+            Percentiles {
+                p10: percentile(&values, 0.10),
+                p50: percentile(&values, 0.50),
+                p90: percentile(&values, 0.90),
+            }
+        })
+        .collect()
+}
+
+/// Standard deviation of month-over-month returns, net of money added or withdrawn in
+/// between. Only consecutive calendar months are compared; returns `None` when fewer than
+/// two returns are available.
+pub fn monthly_volatility(points: &[MonthlyPoint]) -> Option<f64> {
+    let returns: Vec<f64> = points
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, cur) = (pair[0], pair[1]);
+            let consecutive = (cur.year * 12 + cur.month) - (prev.year * 12 + prev.month) == 1;
+            if !consecutive || prev.current <= 0.0 {
+                return None;
+            }
+            let net_flow = cur.invested - prev.invested;
+            Some((cur.current - net_flow) / prev.current - 1.0)
+        })
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (returns.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    // Box-Muller; `1.0 - gen()` keeps the logarithm's argument in (0, 1].
+    let u1: f64 = 1.0 - rng.r#gen::<f64>();
+    let u2: f64 = rng.r#gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn bucket(value: f64, annual_return: f64) -> ProjectionBucket {
+        ProjectionBucket {
+            name: "Stocks".to_string(),
+            value,
+            annual_return,
+        }
+    }
+
+    #[test]
+    fn deterministic_compounds_annual_return_and_adds_contributions() {
+        let params = ProjectionParams {
+            years: 2,
+            monthly_contribution: 0.0,
+            inflation: 0.0,
+        };
+        let path = deterministic(&[bucket(1000.0, 10.0)], &params);
+        assert_eq!(path.len(), 3);
+        assert!((path[1] - 1100.0).abs() < 1e-6);
+        assert!((path[2] - 1210.0).abs() < 1e-6);
+
+        let params = ProjectionParams {
+            years: 1,
+            monthly_contribution: 100.0,
+            inflation: 0.0,
+        };
+        let path = deterministic(&[bucket(0.0, 0.0)], &params);
+        assert!((path[1] - 1200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn monthly_volatility_ignores_contributions_and_gaps() {
+        let point = |year, month, invested, current| MonthlyPoint {
+            month,
+            year,
+            invested,
+            current,
+        };
+        // Every month adds 100 of new money and nothing else happens.
+        let flat = [
+            point(2024, 11, 1000.0, 1000.0),
+            point(2024, 12, 1100.0, 1100.0),
+            point(2025, 1, 1200.0, 1200.0),
+        ];
+        assert_eq!(monthly_volatility(&flat), Some(0.0));
+
+        let gapped = [
+            point(2024, 1, 1000.0, 1000.0),
+            point(2024, 3, 1000.0, 1200.0),
+            point(2024, 5, 1000.0, 900.0),
+        ];
+        assert_eq!(monthly_volatility(&gapped), None);
+    }
+
+    #[test]
+    fn monte_carlo_percentiles_are_ordered_and_seeded() {
+        let params = ProjectionParams {
+            years: 3,
+            monthly_contribution: 50.0,
+            inflation: 0.0,
+        };
+        let buckets = [bucket(1000.0, 7.0)];
+        let first = monte_carlo(&buckets, &params, 0.04, 500, &mut StdRng::seed_from_u64(7));
+        let second = monte_carlo(&buckets, &params, 0.04, 500, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(first.len(), 4);
+        for (a, b) in first.iter().zip(&second) {
+            assert!(a.p10 <= a.p50 && a.p50 <= a.p90);
+            assert_eq!(a.p50, b.p50);
+        }
+    }
+}
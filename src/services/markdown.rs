@@ -0,0 +1,141 @@
+use crate::models::post::{RenderedBody, TocEntry};
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd, html};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// Bumped whenever rendering output changes so cached revisions are re-rendered.
+pub const RENDER_VERSION: i32 = 1;
+
+const WORDS_PER_MINUTE: usize = 200;
+
+static SANITIZER: LazyLock<ammonia::Builder<'static>> = LazyLock::new(|| {
+    let mut builder = ammonia::Builder::default();
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, ["id"]);
+    }
+    builder.add_tag_attributes("input", ["type", "checked", "disabled"]);
+    builder.add_tags(["input"]);
+    builder
+});
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// GitHub-style anchor: lowercase alphanumerics, spaces and dashes become `-`, the rest is
+/// dropped. Repeats get a `-1`, `-2`, ... suffix.
+fn heading_id(text: &str, used: &mut HashSet<String>) -> String {
+    let base: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '_' => Some(c),
+            ' ' | '-' => Some('-'),
+            _ => None,
+        })
+        .collect();
+    let base = if base.is_empty() {
+        "section".to_string()
+    } else {
+        base
+    };
+
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while !used.insert(id.clone()) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+/// Renders Markdown to sanitized HTML, giving every heading an anchor id and collecting
+/// them into a table of contents.
+pub fn render(markdown: &str) -> RenderedBody {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+
+    let mut used_ids = HashSet::new();
+    let mut toc = Vec::new();
+    let mut heading_ids: HashMap<usize, String> = HashMap::new();
+    let mut word_count = 0;
+    let mut open_heading: Option<(usize, u8, String)> = None;
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                open_heading = Some((index, heading_level(*level), String::new()));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((start, level, text)) = open_heading.take() {
+                    let id = heading_id(&text, &mut used_ids);
+                    heading_ids.insert(start, id.clone());
+                    toc.push(TocEntry {
+                        level,
+                        id,
+                        text: text.trim().to_string(),
+                    });
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                word_count += text.split_whitespace().count();
+                if let Some((_, _, heading)) = open_heading.as_mut() {
+                    heading.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+    for (index, id) in heading_ids {
+        if let Event::Start(Tag::Heading { id: heading_id, .. }) = &mut events[index] {
+            *heading_id = Some(CowStr::from(id));
+        }
+    }
+
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events.into_iter());
+
+    RenderedBody {
+        html: SANITIZER.clean(&unsafe_html).to_string(),
+        toc,
+        word_count: word_count as i32,
+        reading_time_minutes: reading_time_minutes(word_count as i32),
+    }
+}
+
+pub fn reading_time_minutes(word_count: i32) -> i32 {
+    if word_count <= 0 {
+        return 0;
+    }
+    (word_count as usize).div_ceil(WORDS_PER_MINUTE).max(1) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_strips_scripts_and_builds_toc() {
+        let rendered = render(
+            "# Intro\n\nHello <script>alert(1)</script> world\n\n## Setup `cargo`\n\n## Intro\n",
+        );
+
+        assert!(!rendered.html.contains("<script"));
+        assert!(rendered.html.contains(r#"<h1 id="intro">"#));
+        let ids: Vec<&str> = rendered.toc.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, vec!["intro", "setup-cargo", "intro-1"]);
+        assert_eq!(rendered.toc[1].level, 2);
+        assert_eq!(rendered.toc[1].text, "Setup cargo");
+        assert_eq!(rendered.reading_time_minutes, 1);
+    }
+}
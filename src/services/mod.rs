@@ -1,13 +1,32 @@
 pub mod auth;
 pub mod bookmark;
 pub mod comment;
+pub mod comment_moderation;
+pub mod comment_reaction;
+pub mod counter;
+pub mod feed;
 pub mod holding;
+pub mod holding_audit;
+pub mod holding_projection;
+pub mod markdown;
 pub mod notification;
 pub mod post;
+pub mod post_archive;
 pub mod post_like;
+pub mod post_revision;
+pub mod post_share;
+pub mod post_trash;
 pub mod post_view;
 pub mod report;
+pub mod series;
+pub mod sitemap;
+pub mod slug_history;
+pub mod storage;
 pub mod tag;
+pub mod timeline;
+pub mod trending;
+pub mod upload;
 pub mod user;
 pub mod user_follow;
 pub mod user_hydration;
+pub mod visibility;
@@ -1,36 +1,69 @@
 use crate::entities::notifications;
 use crate::models::notification::{MarkAllReadResponse, NotificationResponse, UnreadCountResponse};
+use crate::pagination::{self, Cursor, Page, Paged};
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, Set,
 };
+use serde_json::Value;
 use uuid::Uuid;
 
+pub struct NewNotification<'a> {
+    pub notification_type: &'a str,
+    pub title: &'a str,
+    pub message: Option<&'a str>,
+    pub data: Option<Value>,
+}
+
+/// Sends the same notification to every user in `user_ids`.
+pub async fn notify_users<C: ConnectionTrait>(
+    conn: &C,
+    user_ids: &[Uuid],
+    notification: &NewNotification<'_>,
+) -> Result<(), DbErr> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let data = notification.data.as_ref().map(Value::to_string);
+    let rows = user_ids.iter().map(|user_id| notifications::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(*user_id),
+        r#type: Set(notification.notification_type.to_string()),
+        title: Set(notification.title.to_string()),
+        message: Set(notification.message.map(ToOwned::to_owned)),
+        read: Set(false),
+        data: Set(data.clone()),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+    });
+    notifications::Entity::insert_many(rows).exec(conn).await?;
+    Ok(())
+}
+
 pub async fn get_notifications(
     db: &DatabaseConnection,
     user_id: Uuid,
     unread_only: bool,
-    limit: i64,
-    offset: i64,
-) -> Result<(Vec<NotificationResponse>, i64), DbErr> {
+    page: &Page,
+) -> Result<Paged<NotificationResponse>, DbErr> {
     let mut query = notifications::Entity::find().filter(notifications::Column::UserId.eq(user_id));
     if unread_only {
         query = query.filter(notifications::Column::Read.eq(false));
     }
 
-    let total = query.clone().count(db).await? as i64;
-    let notifications = query
-        .order_by_desc(notifications::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?
-        .into_iter()
-        .map(Into::into)
-        .collect();
+    let notifications = pagination::fetch_page(
+        db,
+        query,
+        (notifications::Column::CreatedAt, notifications::Column::Id),
+        page,
+        |notification| Some(Cursor::new(notification.created_at, notification.id)),
+    )
+    .await?;
 
-    Ok((notifications, total))
+    Ok(notifications.map(Into::into))
 }
 
 pub async fn get_unread_count(
@@ -1,11 +1,15 @@
 use crate::entities::{posts, posts_to_tags, tags, users};
-use crate::models::post::{Post, SitemapPost};
-use chrono::Utc;
+use crate::models::post::{Post, PostSearchMatch, SitemapPost};
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::visibility::{self, PostAccess, PostVisibility};
+use crate::services::{post_revision, series, slug_history};
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult,
-    IntoActiveModel, ModelTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, FromQueryResult, IntoActiveModel, ModelTrait, Order, QueryFilter, QueryOrder,
+    QuerySelect, Select, Set, TransactionTrait, prelude::Expr,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Copy)]
 pub enum SortDirection {
@@ -25,6 +29,37 @@ fn validate_order_field(order_by: Option<&str>) -> posts::Column {
     }
 }
 
+/// A post is visible in listings once it is published and its `published_at`, if scheduled,
+/// has passed.
+pub(crate) fn is_live() -> Condition {
+    Condition::all()
+        .add(posts::Column::Published.eq(true))
+        .add(posts::Column::DeletedAt.is_null())
+        .add(
+            Condition::any()
+                .add(posts::Column::PublishedAt.is_null())
+                .add(posts::Column::PublishedAt.lte(Utc::now())),
+        )
+}
+
+/// Live and public: what anonymous listings, feeds, trending and the sitemap show.
+pub(crate) fn is_listed() -> Condition {
+    is_live().add(visibility::listed_for(None))
+}
+
+macro_rules! live_post_sql {
+    () => {
+        "p.published = true AND p.deleted_at IS NULL \
+         AND (p.published_at IS NULL OR p.published_at <= NOW())"
+    };
+}
+
+/// SQL twin of `is_live` for raw queries that alias `posts` as `p`.
+pub(crate) const LIVE_POST_SQL: &str = live_post_sql!();
+
+/// SQL twin of `is_listed` for raw queries that alias `posts` as `p`.
+pub(crate) const LISTED_POST_SQL: &str = concat!(live_post_sql!(), " AND p.visibility = 'public'");
+
 fn get_order_dir(dir: Option<SortDirection>) -> Order {
     match dir {
         Some(SortDirection::Asc) => Order::Asc,
@@ -32,6 +67,105 @@ fn get_order_dir(dir: Option<SortDirection>) -> Order {
     }
 }
 
+/// Weighted document searched by `websearch_to_tsquery`: title (A), tag names (B), body (C).
+const SEARCH_DOCUMENT: &str = "setweight(to_tsvector('english', coalesce(posts.title, '')), 'A') \
+    || setweight(to_tsvector('english', coalesce((SELECT string_agg(t.name, ' ') FROM posts_to_tags ptt \
+    INNER JOIN tags t ON ptt.tag_id = t.id WHERE ptt.post_id = posts.id), '')), 'B') \
+    || setweight(to_tsvector('english', coalesce(posts.body, '')), 'C')";
+
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2";
+
+fn search_term(search: Option<&str>) -> Option<&str> {
+    search.map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Restricts `query` to posts matching `search` and, unless the caller asked for an explicit
+/// ordering, sorts them by relevance.
+fn apply_search(
+    query: Select<posts::Entity>,
+    search: Option<&str>,
+    order_by: Option<&str>,
+    order_direction: Option<SortDirection>,
+) -> Select<posts::Entity> {
+    let Some(search) = search else {
+        return query.order_by(
+            validate_order_field(order_by),
+            get_order_dir(order_direction),
+        );
+    };
+
+    let query = query.filter(Expr::cust_with_values(
+        format!("({SEARCH_DOCUMENT}) @@ websearch_to_tsquery('english', $1)"),
+        [search],
+    ));
+    if order_by.is_some() {
+        return query.order_by(
+            validate_order_field(order_by),
+            get_order_dir(order_direction),
+        );
+    }
+    query
+        .order_by(
+            Expr::cust_with_values(
+                format!("ts_rank_cd({SEARCH_DOCUMENT}, websearch_to_tsquery('english', $1))"),
+                [search],
+            ),
+            Order::Desc,
+        )
+        .order_by(posts::Column::CreatedAt, Order::Desc)
+}
+
+/// Fills `Post::search` with the rank and `<mark>`-highlighted title and body fragments.
+async fn attach_search_matches(
+    db: &DatabaseConnection,
+    posts: &mut [Post],
+    search: &str,
+) -> Result<(), DbErr> {
+    if posts.is_empty() {
+        return Ok(());
+    }
+
+    #[derive(FromQueryResult)]
+    struct SearchMatchRow {
+        id: uuid::Uuid,
+        rank: f32,
+        title: String,
+        snippet: String,
+    }
+
+    let post_ids: Vec<uuid::Uuid> = posts.iter().map(|p| p.id).collect();
+    let sql = format!(
+        "WITH q AS (SELECT websearch_to_tsquery('english', $2) AS query) \
+         SELECT posts.id, ts_rank_cd({SEARCH_DOCUMENT}, q.query) AS rank, \
+         ts_headline('english', posts.title, q.query, 'HighlightAll=true, StartSel=<mark>, StopSel=</mark>') AS title, \
+         ts_headline('english', coalesce(posts.body, ''), q.query, '{HEADLINE_OPTIONS}') AS snippet \
+         FROM posts, q WHERE posts.id = ANY($1)"
+    );
+    let rows: HashMap<uuid::Uuid, SearchMatchRow> =
+        SearchMatchRow::find_by_statement(sea_orm::Statement::from_sql_and_values(
+            sea_orm::DbBackend::Postgres,
+            sql,
+            vec![post_ids.into(), search.into()],
+        ))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row))
+        .collect();
+
+    for post in posts.iter_mut() {
+        if let Some(row) = rows.get(&post.id) {
+            post.search = Some(PostSearchMatch {
+                rank: row.rank,
+                title: row.title.clone(),
+                snippet: row.snippet.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
 async fn hydrate_post(
     post: &posts::Model,
     user: Option<users::Model>,
@@ -41,7 +175,7 @@ async fn hydrate_post(
     Ok(Post::from_entity(post.clone(), user, tags, truncate_body))
 }
 
-async fn hydrate_posts(
+pub(crate) async fn hydrate_posts(
     db: &DatabaseConnection,
     posts: Vec<posts::Model>,
     truncate_body: bool,
@@ -102,39 +236,81 @@ async fn hydrate_posts(
     Ok(hydrated)
 }
 
-pub async fn get_all_posts(
-    db: &DatabaseConnection,
-    offset: i64,
-    limit: i64,
+/// Whether a listing is in the default newest-first order, the only one a cursor can page.
+pub fn is_keyset_order(
     search: Option<&str>,
     order_by: Option<&str>,
     order_direction: Option<SortDirection>,
-) -> Result<(Vec<Post>, i64), DbErr> {
-    let mut query = posts::Entity::find()
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null());
+) -> bool {
+    search_term(search).is_none()
+        && matches!(order_by, None | Some("created_at"))
+        && !matches!(order_direction, Some(SortDirection::Asc))
+}
 
-    if let Some(search) = search.filter(|s| !s.trim().is_empty()) {
-        query = query.filter(posts::Column::Title.contains(search));
-    }
+fn post_cursor(post: &posts::Model) -> Option<Cursor> {
+    post.created_at
+        .map(|created_at| Cursor::new(created_at, post.id))
+}
 
-    let total = query.clone().count(db).await? as i64;
-    let post_models = query
-        .order_by(
-            validate_order_field(order_by),
-            get_order_dir(order_direction),
+/// Pages a post listing by keyset in the default order and by offset for search or custom
+/// orderings.
+async fn fetch_listing(
+    db: &DatabaseConnection,
+    query: Select<posts::Entity>,
+    page: &Page,
+    search: Option<&str>,
+    order_by: Option<&str>,
+    order_direction: Option<SortDirection>,
+) -> Result<Paged<Post>, DbErr> {
+    let search = search_term(search);
+    let paged = if is_keyset_order(search, order_by, order_direction) {
+        pagination::fetch_page(
+            db,
+            query,
+            (posts::Column::CreatedAt, posts::Column::Id),
+            page,
+            post_cursor,
         )
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
+        .await?
+    } else {
+        let query = apply_search(query, search, order_by, order_direction);
+        pagination::fetch_offset(db, query, page).await?
+    };
 
-    Ok((hydrate_posts(db, post_models, true).await?, total))
+    let Paged {
+        items,
+        total,
+        next_cursor,
+    } = paged;
+    let mut posts = hydrate_posts(db, items, true).await?;
+    if let Some(search) = search {
+        attach_search_matches(db, &mut posts, search).await?;
+    }
+    Ok(Paged {
+        items: posts,
+        total,
+        next_cursor,
+    })
 }
 
+pub async fn get_all_posts(
+    db: &DatabaseConnection,
+    page: &Page,
+    search: Option<&str>,
+    order_by: Option<&str>,
+    order_direction: Option<SortDirection>,
+) -> Result<Paged<Post>, DbErr> {
+    let query = posts::Entity::find().filter(is_listed());
+    fetch_listing(db, query, page, search, order_by, order_direction).await
+}
+
+/// Detail lookup; with `render` the Markdown body is rendered into `Post::rendered`. Posts
+/// `access` may not read are reported as missing.
 pub async fn get_post_by_id(
     db: &DatabaseConnection,
     id: uuid::Uuid,
+    render: bool,
+    access: &PostAccess<'_>,
 ) -> Result<Option<Post>, DbErr> {
     let post = posts::Entity::find_by_id(id)
         .filter(posts::Column::DeletedAt.is_null())
@@ -142,21 +318,85 @@ pub async fn get_post_by_id(
         .await?;
 
     match post {
-        Some(post) => {
+        Some(post) if visibility::can_view(db, &post, access).await? => {
             let user = post.find_related(users::Entity).one(db).await?;
             let tags = post.find_related(tags::Entity).all(db).await?;
-            Ok(Some(hydrate_post(&post, user, tags, false).await?))
+            let mut hydrated = hydrate_post(&post, user, tags, false).await?;
+            if render {
+                hydrated.rendered = Some(post_revision::rendered_body(db, &post).await?);
+            }
+            hydrated.series = series::post_navigation(db, post.id).await?;
+            Ok(Some(hydrated))
         }
-        None => Ok(None),
+        _ => Ok(None),
     }
 }
 
+pub enum FeedScope<'a> {
+    Site,
+    Author(&'a str),
+    Tag(&'a str),
+}
+
+/// Most recently published live posts with full bodies and cached renders for syndication
+/// feeds. Returns `None` when the author or tag does not exist.
+pub async fn get_feed_posts(
+    db: &DatabaseConnection,
+    scope: FeedScope<'_>,
+    limit: i64,
+) -> Result<Option<Vec<Post>>, DbErr> {
+    let query = match scope {
+        FeedScope::Site => posts::Entity::find(),
+        FeedScope::Author(username) => {
+            let Some(user) = users::Entity::find()
+                .filter(users::Column::Username.eq(username))
+                .filter(users::Column::DeletedAt.is_null())
+                .one(db)
+                .await?
+            else {
+                return Ok(None);
+            };
+            user.find_related(posts::Entity)
+        }
+        FeedScope::Tag(name) => {
+            let Some(tag) = tags::Entity::find()
+                .filter(tags::Column::Name.eq(name))
+                .one(db)
+                .await?
+            else {
+                return Ok(None);
+            };
+            tag.find_related(posts::Entity)
+        }
+    };
+
+    let post_models = query
+        .filter(is_listed())
+        .order_by(
+            Expr::cust("COALESCE(posts.published_at, posts.created_at)"),
+            Order::Desc,
+        )
+        .order_by_desc(posts::Column::Id)
+        .limit(limit.max(0) as u64)
+        .all(db)
+        .await?;
+
+    let mut rendered = post_revision::rendered_bodies(db, &post_models).await?;
+    let mut posts = hydrate_posts(db, post_models, false).await?;
+    for post in &mut posts {
+        post.rendered = rendered.remove(&post.id);
+    }
+    Ok(Some(posts))
+}
+
+/// An author's live posts; followers-only posts are included when `viewer` follows the
+/// author or is the author.
 pub async fn get_posts_by_username(
     db: &DatabaseConnection,
     username: &str,
-    offset: i64,
-    limit: i64,
-) -> Result<(Vec<Post>, i64), DbErr> {
+    page: &Page,
+    viewer: Option<uuid::Uuid>,
+) -> Result<Paged<Post>, DbErr> {
     let user = users::Entity::find()
         .filter(users::Column::Username.eq(username))
         .filter(users::Column::DeletedAt.is_null())
@@ -164,30 +404,20 @@ pub async fn get_posts_by_username(
         .await?;
 
     let Some(user) = user else {
-        return Ok((Vec::new(), 0));
+        return Ok(Paged::empty(page));
     };
 
     let query = user
         .clone()
         .find_related(posts::Entity)
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null());
-
-    let total = query.clone().count(db).await? as i64;
-    let post_models = query
-        .order_by_desc(posts::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-
-    Ok((hydrate_posts(db, post_models, true).await?, total))
+        .filter(is_live())
+        .filter(visibility::listed_for(viewer));
+    fetch_listing(db, query, page, None, None, None).await
 }
 
 pub async fn get_random_posts(db: &DatabaseConnection, limit: i64) -> Result<Vec<Post>, DbErr> {
     let post_models = posts::Entity::find()
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null())
+        .filter(is_listed())
         .order_by(sea_orm::sea_query::Expr::cust("RANDOM()"), Order::Asc)
         .limit(limit.max(0) as u64)
         .all(db)
@@ -196,19 +426,87 @@ pub async fn get_random_posts(db: &DatabaseConnection, limit: i64) -> Result<Vec
     hydrate_posts(db, post_models, true).await
 }
 
-pub async fn get_trending_posts(db: &DatabaseConnection, limit: i64) -> Result<Vec<Post>, DbErr> {
-    let post_models = posts::Entity::find()
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null())
-        .order_by(
-            sea_orm::sea_query::Expr::cust("like_count * 2 + bookmark_count * 2 + view_count"),
-            Order::Desc,
-        )
-        .limit(limit.max(0) as u64)
+/// Scores other live posts by shared tags (rarer tags weigh more), same author, and
+/// co-engagement from users who liked or bookmarked this post. Returns `None` when the post
+/// does not exist, `access` may not read it, or it is not live and `access` is not its author.
+pub async fn get_related_posts(
+    db: &DatabaseConnection,
+    post_id: uuid::Uuid,
+    limit: i64,
+    access: &PostAccess<'_>,
+) -> Result<Option<Vec<Post>>, DbErr> {
+    let Some(post) = visibility::find_viewable(db, post_id, access).await? else {
+        return Ok(None);
+    };
+    let live = post.published == Some(true)
+        && post
+            .published_at
+            .is_none_or(|published_at| published_at <= Utc::now());
+    if !live && access.viewer != Some(post.created_by) {
+        return Ok(None);
+    }
+
+    #[derive(FromQueryResult)]
+    struct RelatedRow {
+        id: uuid::Uuid,
+    }
+
+    let sql = format!(
+        "WITH tag_counts AS ( \
+             SELECT tag_id, COUNT(*)::float8 AS n FROM posts_to_tags GROUP BY tag_id \
+         ), corpus AS ( \
+             SELECT GREATEST(COUNT(*), 1)::float8 AS n FROM posts p WHERE {LISTED_POST_SQL} \
+         ), tag_scores AS ( \
+             SELECT other.post_id, SUM(LN(1 + corpus.n / tag_counts.n)) AS score \
+             FROM posts_to_tags mine \
+             INNER JOIN posts_to_tags other ON other.tag_id = mine.tag_id AND other.post_id <> $1 \
+             INNER JOIN tag_counts ON tag_counts.tag_id = mine.tag_id \
+             CROSS JOIN corpus \
+             WHERE mine.post_id = $1 \
+             GROUP BY other.post_id \
+         ), engagement AS ( \
+             SELECT post_id, user_id FROM post_likes WHERE deleted_at IS NULL \
+             UNION SELECT post_id, user_id FROM post_bookmarks \
+         ), co_engagement AS ( \
+             SELECT e.post_id, COUNT(DISTINCT e.user_id)::float8 AS n \
+             FROM engagement e \
+             WHERE e.post_id <> $1 \
+             AND e.user_id IN (SELECT user_id FROM engagement WHERE post_id = $1) \
+             GROUP BY e.post_id \
+         ) \
+         SELECT p.id FROM posts p \
+         LEFT JOIN tag_scores ts ON ts.post_id = p.id \
+         LEFT JOIN co_engagement ce ON ce.post_id = p.id \
+         WHERE {LISTED_POST_SQL} AND p.id <> $1 \
+         AND (ts.post_id IS NOT NULL OR ce.post_id IS NOT NULL OR p.created_by = $2) \
+         ORDER BY COALESCE(ts.score, 0) * 2.0 \
+             + CASE WHEN p.created_by = $2 THEN 1.5 ELSE 0 END \
+             + LN(1 + COALESCE(ce.n, 0)) * 1.5 DESC, \
+             p.created_at DESC \
+         LIMIT $3"
+    );
+    let ids: Vec<uuid::Uuid> =
+        RelatedRow::find_by_statement(sea_orm::Statement::from_sql_and_values(
+            sea_orm::DbBackend::Postgres,
+            sql,
+            vec![post.id.into(), post.created_by.into(), limit.max(0).into()],
+        ))
         .all(db)
-        .await?;
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect();
 
-    hydrate_posts(db, post_models, true).await
+    let mut by_id: HashMap<uuid::Uuid, posts::Model> = posts::Entity::find()
+        .filter(posts::Column::Id.is_in(ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|post| (post.id, post))
+        .collect();
+    let ordered = ids.iter().filter_map(|id| by_id.remove(id)).collect();
+
+    Ok(Some(hydrate_posts(db, ordered, true).await?))
 }
 
 pub async fn get_posts_for_sitemap(
@@ -216,8 +514,7 @@ pub async fn get_posts_for_sitemap(
     limit: i64,
 ) -> Result<Vec<SitemapPost>, DbErr> {
     let post_models = posts::Entity::find()
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null())
+        .filter(is_listed())
         .order_by_desc(posts::Column::CreatedAt)
         .limit(limit.max(0) as u64)
         .all(db)
@@ -249,6 +546,8 @@ pub async fn get_post_by_username_and_slug(
     db: &DatabaseConnection,
     username: &str,
     slug: &str,
+    render: bool,
+    access: &PostAccess<'_>,
 ) -> Result<Option<Post>, DbErr> {
     let user = users::Entity::find()
         .filter(users::Column::Username.eq(username))
@@ -263,25 +562,32 @@ pub async fn get_post_by_username_and_slug(
     let post = user
         .find_related(posts::Entity)
         .filter(posts::Column::Slug.eq(slug))
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null())
+        .filter(is_live())
         .one(db)
         .await?;
 
     match post {
-        Some(post) => {
+        Some(post) if visibility::can_view(db, &post, access).await? => {
             let user = post.find_related(users::Entity).one(db).await?;
             let tags = post.find_related(tags::Entity).all(db).await?;
-            Ok(Some(hydrate_post(&post, user, tags, false).await?))
+            let mut hydrated = hydrate_post(&post, user, tags, false).await?;
+            if render {
+                hydrated.rendered = Some(post_revision::rendered_body(db, &post).await?);
+            }
+            hydrated.series = series::post_navigation(db, post.id).await?;
+            Ok(Some(hydrated))
         }
-        None => Ok(None),
+        _ => Ok(None),
     }
 }
 
-async fn find_or_create_tag(db: &DatabaseConnection, name: &str) -> Result<tags::Model, DbErr> {
+async fn find_or_create_tag<C: ConnectionTrait>(
+    conn: &C,
+    name: &str,
+) -> Result<tags::Model, DbErr> {
     if let Some(tag) = tags::Entity::find()
         .filter(tags::Column::Name.eq(name))
-        .one(db)
+        .one(conn)
         .await?
     {
         return Ok(tag);
@@ -292,7 +598,7 @@ async fn find_or_create_tag(db: &DatabaseConnection, name: &str) -> Result<tags:
         created_at: Set(Some(Utc::now().into())),
         ..Default::default()
     }
-    .insert(db)
+    .insert(conn)
     .await
 }
 
@@ -313,23 +619,23 @@ fn normalize_tag_names(tag_names: &[String]) -> Vec<String> {
     normalized
 }
 
-async fn replace_post_tags(
-    db: &DatabaseConnection,
+async fn replace_post_tags<C: ConnectionTrait>(
+    conn: &C,
     post_id: uuid::Uuid,
     tag_names: &[String],
 ) -> Result<(), DbErr> {
     posts_to_tags::Entity::delete_many()
         .filter(posts_to_tags::Column::PostId.eq(post_id))
-        .exec(db)
+        .exec(conn)
         .await?;
 
     for name in normalize_tag_names(tag_names) {
-        let tag = find_or_create_tag(db, &name).await?;
+        let tag = find_or_create_tag(conn, &name).await?;
         posts_to_tags::ActiveModel {
             post_id: Set(post_id),
             tag_id: Set(tag.id),
         }
-        .insert(db)
+        .insert(conn)
         .await?;
     }
 
@@ -342,7 +648,11 @@ pub struct CreatePostInput {
     pub slug: String,
     pub body: String,
     pub published: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    pub visibility: PostVisibility,
     pub tags: Vec<String>,
+    /// When false the post is stored as already announced, so followers are never notified.
+    pub notify_followers: bool,
 }
 
 pub struct UpdatePostInput {
@@ -351,6 +661,8 @@ pub struct UpdatePostInput {
     pub slug: Option<String>,
     pub body: Option<String>,
     pub published: Option<bool>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub visibility: Option<PostVisibility>,
     pub tags: Option<Vec<String>>,
 }
 
@@ -368,17 +680,27 @@ pub async fn create_post(
         slug: Set(input.slug),
         photo_url: Set(input.photo_url),
         published: Set(Some(input.published)),
+        published_at: Set(input
+            .published_at
+            .or(input.published.then_some(now))
+            .map(Into::into)),
         created_at: Set(Some(now.into())),
         updated_at: Set(Some(now.into())),
         view_count: Set(Some(0)),
         like_count: Set(Some(0)),
         bookmark_count: Set(Some(0)),
+        visibility: Set(input.visibility.as_str().to_string()),
+        version: Set(1),
+        comments_locked: Set(false),
+        comment_approval_required: Set(false),
+        published_notified_at: Set((!input.notify_followers).then(|| now.into())),
         ..Default::default()
     }
     .insert(db)
     .await?;
 
     replace_post_tags(db, post.id, &input.tags).await?;
+    post_revision::record(db, &post, creator_id).await?;
     let user = post.find_related(users::Entity).one(db).await?;
     let tags = post.find_related(tags::Entity).all(db).await?;
     hydrate_post(&post, user, tags, false).await
@@ -397,19 +719,68 @@ pub async fn is_author(
     Ok(post.map(|post| post.created_by == user_id))
 }
 
+#[derive(Debug)]
+pub enum UpdatePostError {
+    Db(DbErr),
+    PostNotFound,
+    /// The post changed since the client read it.
+    VersionMismatch {
+        current: i32,
+    },
+}
+
+impl From<DbErr> for UpdatePostError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+async fn current_version(
+    db: &DatabaseConnection,
+    post_id: uuid::Uuid,
+) -> Result<i32, UpdatePostError> {
+    posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .map(|post| post.version)
+        .ok_or(UpdatePostError::PostNotFound)
+}
+
+/// Applies `input` when the post is still at `expected_version` (any version when `None`).
+/// The version check is repeated in the `UPDATE` itself so concurrent writers cannot both win,
+/// and the save, its revision, slug history and tags are written in one transaction.
 pub async fn update_post(
     db: &DatabaseConnection,
     post_id: uuid::Uuid,
     input: UpdatePostInput,
-) -> Result<Option<Post>, DbErr> {
+    expected_version: Option<i32>,
+) -> Result<Post, UpdatePostError> {
     let Some(post) = posts::Entity::find_by_id(post_id)
         .filter(posts::Column::DeletedAt.is_null())
         .one(db)
         .await?
     else {
-        return Ok(None);
+        return Err(UpdatePostError::PostNotFound);
     };
+    if let Some(expected) = expected_version
+        && expected != post.version
+    {
+        return Err(UpdatePostError::VersionMismatch {
+            current: post.version,
+        });
+    }
 
+    let txn = db.begin().await?;
+    post_revision::record_baseline(&txn, &post).await?;
+
+    let version = post.version;
+    let post_published_at = post.published_at;
+    // `published` is NULL on posts that predate drafts; those count as published.
+    let was_published = post.published != Some(false);
+    let legacy_unannounced =
+        was_published && post_published_at.is_none() && post.published_notified_at.is_none();
+    let old_slug = post.slug.clone();
     let mut active = post.into_active_model();
     if let Some(title) = input.title.filter(|value| !value.trim().is_empty()) {
         active.title = Set(title);
@@ -423,19 +794,55 @@ pub async fn update_post(
     if input.photo_url.is_some() {
         active.photo_url = Set(input.photo_url);
     }
+    if let Some(published_at) = input.published_at {
+        active.published_at = Set(Some(published_at.into()));
+        // A post that was already live before publish moments existed was never announced;
+        // dating it now must not make the publish job announce it as new.
+        if legacy_unannounced {
+            active.published_notified_at = Set(Some(Utc::now().into()));
+        }
+    }
+    if let Some(visibility) = input.visibility {
+        active.visibility = Set(visibility.as_str().to_string());
+    }
     if let Some(published) = input.published {
         active.published = Set(Some(published));
+        // Only a draft going live gets a publish moment; re-saving a live post does not.
+        if published
+            && !was_published
+            && input.published_at.is_none()
+            && post_published_at.is_none()
+        {
+            active.published_at = Set(Some(Utc::now().into()));
+        }
     }
     active.updated_at = Set(Some(Utc::now().into()));
+    active.version = Set(version + 1);
 
-    let post = active.update(db).await?;
+    let post = match posts::Entity::update(active)
+        .filter(posts::Column::Version.eq(version))
+        .exec(&txn)
+        .await
+    {
+        Ok(post) => post,
+        Err(DbErr::RecordNotUpdated) => {
+            txn.rollback().await?;
+            return Err(UpdatePostError::VersionMismatch {
+                current: current_version(db, post_id).await?,
+            });
+        }
+        Err(err) => return Err(err.into()),
+    };
+    slug_history::record_post_slug(&txn, post.id, &old_slug, &post.slug).await?;
+    post_revision::record(&txn, &post, post.created_by).await?;
     if let Some(tags) = input.tags {
-        replace_post_tags(db, post.id, &tags).await?;
+        replace_post_tags(&txn, post.id, &tags).await?;
     }
+    txn.commit().await?;
 
     let user = post.find_related(users::Entity).one(db).await?;
     let tags = post.find_related(tags::Entity).all(db).await?;
-    Ok(Some(hydrate_post(&post, user, tags, false).await?))
+    Ok(hydrate_post(&post, user, tags, false).await?)
 }
 
 pub async fn soft_delete_post(db: &DatabaseConnection, post_id: uuid::Uuid) -> Result<bool, DbErr> {
@@ -457,38 +864,22 @@ pub async fn soft_delete_post(db: &DatabaseConnection, post_id: uuid::Uuid) -> R
 pub async fn get_posts_by_tag(
     db: &DatabaseConnection,
     tag_name: &str,
-    offset: i64,
-    limit: i64,
-    _search: Option<&str>,
+    page: &Page,
+    search: Option<&str>,
     order_by: Option<&str>,
     order_direction: Option<SortDirection>,
-) -> Result<(Vec<Post>, i64), DbErr> {
+) -> Result<Paged<Post>, DbErr> {
     let tag = tags::Entity::find()
         .filter(tags::Column::Name.eq(tag_name))
         .one(db)
         .await?;
 
     let Some(tag) = tag else {
-        return Ok((Vec::new(), 0));
+        return Ok(Paged::empty(page));
     };
 
-    let query = tag
-        .find_related(posts::Entity)
-        .filter(posts::Column::Published.eq(true))
-        .filter(posts::Column::DeletedAt.is_null());
-
-    let total = query.clone().count(db).await? as i64;
-    let post_models = query
-        .order_by(
-            validate_order_field(order_by),
-            get_order_dir(order_direction),
-        )
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-
-    Ok((hydrate_posts(db, post_models, true).await?, total))
+    let query = tag.find_related(posts::Entity).filter(is_listed());
+    fetch_listing(db, query, page, search, order_by, order_direction).await
 }
 
 #[cfg(test)]
@@ -0,0 +1,457 @@
+//! Markdown archives in the layout static site generators use: one `.md` file per post with
+//! YAML front matter (`title`, `slug`, `tags`, `published`, `date`, `visibility`) followed by
+//! the body.
+
+use crate::dto::post::CreatePostRequest;
+use crate::entities::{posts, tags, users};
+use crate::models::post_archive::{ImportEntryReport, ImportReport, ImportStatus};
+use crate::services::post::{self, CreatePostInput};
+use crate::services::visibility::PostVisibility;
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+use uuid::Uuid;
+use validator::Validate;
+use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+const MAX_ENTRIES: usize = 1000;
+/// Uncompressed size limit per file, so a small archive cannot expand without bound.
+const MAX_ENTRY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Db(DbErr),
+    InvalidArchive(String),
+    UserNotFound,
+}
+
+impl From<DbErr> for ArchiveError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(err: zip::result::ZipError) -> Self {
+        Self::InvalidArchive(err.to_string())
+    }
+}
+
+/// Jekyll and Hugo accept tags as a list or a single comma-separated string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TagList {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl TagList {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::List(tags) => tags,
+            Self::Joined(tags) => tags.split(',').map(|tag| tag.trim().to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ImportFrontMatter {
+    title: Option<String>,
+    slug: Option<String>,
+    tags: Option<TagList>,
+    #[serde(default)]
+    published: bool,
+    date: Option<String>,
+    #[serde(default)]
+    visibility: PostVisibility,
+}
+
+#[derive(Serialize)]
+struct ExportFrontMatter<'a> {
+    title: &'a str,
+    slug: &'a str,
+    tags: Vec<&'a str>,
+    published: bool,
+    date: String,
+    visibility: PostVisibility,
+}
+
+/// Splits `---\n<yaml>\n---\n<body>`. Files without front matter are all body.
+fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let content = content.trim_start_matches('\u{feff}');
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM:SS [+ZZZZ]` and bare dates (midnight UTC).
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z"))
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|date| date.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+/// `2024-01-05-Hello World.md` becomes `hello-world`.
+fn slug_from_file_name(path: &str) -> String {
+    let stem = path.rsplit('/').next().unwrap_or(path);
+    let stem = stem.rsplit_once('.').map_or(stem, |(stem, _)| stem);
+    let stem = match (stem.get(..10), stem.get(10..11)) {
+        (Some(date), Some("-")) if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => {
+            &stem[11..]
+        }
+        _ => stem,
+    };
+    stem.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn validation_messages(errors: &validator::ValidationErrors) -> Vec<String> {
+    let mut messages: Vec<String> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |error| match &error.message {
+                Some(message) => format!("{field}: {message}"),
+                None => format!("{field} is invalid ({})", error.code),
+            })
+        })
+        .collect();
+    messages.sort();
+    messages
+}
+
+struct ParsedEntry {
+    file: String,
+    slug: Option<String>,
+    request: Result<CreatePostRequest, Vec<String>>,
+}
+
+fn parse_entry(file: String, content: &str) -> ParsedEntry {
+    let (front_matter, body) = split_front_matter(content);
+    let Some(front_matter) = front_matter else {
+        return ParsedEntry {
+            slug: None,
+            file,
+            request: Err(vec!["missing YAML front matter".to_string()]),
+        };
+    };
+    let front_matter: ImportFrontMatter = match serde_yaml::from_str(front_matter) {
+        Ok(front_matter) => front_matter,
+        Err(err) => {
+            return ParsedEntry {
+                slug: None,
+                file,
+                request: Err(vec![format!("invalid front matter: {err}")]),
+            };
+        }
+    };
+
+    let slug = front_matter
+        .slug
+        .filter(|slug| !slug.trim().is_empty())
+        .unwrap_or_else(|| slug_from_file_name(&file));
+    let mut errors = Vec::new();
+    let published_at = match front_matter.date.as_deref() {
+        Some(date) => {
+            let parsed = parse_date(date);
+            if parsed.is_none() {
+                errors.push(format!("date: unrecognised date \"{date}\""));
+            }
+            parsed
+        }
+        None => None,
+    };
+    let request = CreatePostRequest {
+        title: front_matter.title.unwrap_or_default(),
+        photo_url: None,
+        slug: slug.clone(),
+        body: body.trim().to_string(),
+        published: front_matter.published,
+        // A draft keeps no publish date, as with posts created through the API.
+        published_at: published_at.filter(|_| front_matter.published),
+        visibility: front_matter.visibility,
+        tags: front_matter.tags.map(TagList::into_vec).unwrap_or_default(),
+    };
+    if let Err(err) = request.validate() {
+        errors.extend(validation_messages(&err));
+    }
+
+    ParsedEntry {
+        file,
+        slug: Some(slug),
+        request: if errors.is_empty() {
+            Ok(request)
+        } else {
+            Err(errors)
+        },
+    }
+}
+
+fn is_markdown(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    (lower.ends_with(".md") || lower.ends_with(".markdown"))
+        && !lower
+            .split('/')
+            .any(|segment| segment.starts_with('.') || segment == "__macosx")
+}
+
+fn read_archive(data: &[u8]) -> Result<Vec<ParsedEntry>, ArchiveError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+        if file.is_dir() || !is_markdown(file.name()) {
+            continue;
+        }
+        if entries.len() == MAX_ENTRIES {
+            return Err(ArchiveError::InvalidArchive(format!(
+                "archive contains more than {MAX_ENTRIES} Markdown files"
+            )));
+        }
+        let name = file.name().to_string();
+        if file.size() > MAX_ENTRY_BYTES {
+            entries.push(ParsedEntry {
+                file: name,
+                slug: None,
+                request: Err(vec![format!("file exceeds {MAX_ENTRY_BYTES} bytes")]),
+            });
+            continue;
+        }
+        let mut content = String::new();
+        // The declared size can lie, so the read is capped as well.
+        if file
+            .take(MAX_ENTRY_BYTES + 1)
+            .read_to_string(&mut content)
+            .is_err()
+            || content.len() as u64 > MAX_ENTRY_BYTES
+        {
+            entries.push(ParsedEntry {
+                file: name,
+                slug: None,
+                request: Err(vec![
+                    "file is not valid UTF-8 text or is too large".to_string(),
+                ]),
+            });
+            continue;
+        }
+        entries.push(parse_entry(name, &content));
+    }
+    Ok(entries)
+}
+
+/// Creates a post through `post::create_post` for every valid file in the zip. Files with
+/// errors, or whose slug already exists, are reported and skipped; the rest are still
+/// imported, and a post that fails to save is reported without stopping the import.
+/// Imported posts are never announced to followers. With `dry_run` nothing is written.
+pub async fn import_posts(
+    db: &DatabaseConnection,
+    author_id: Uuid,
+    data: Bytes,
+    dry_run: bool,
+) -> Result<ImportReport, ArchiveError> {
+    let entries = tokio::task::spawn_blocking(move || read_archive(&data))
+        .await
+        .map_err(|err| ArchiveError::InvalidArchive(err.to_string()))??;
+
+    let slugs: Vec<String> = entries.iter().filter_map(|e| e.slug.clone()).collect();
+    let mut taken: HashSet<String> = if slugs.is_empty() {
+        HashSet::new()
+    } else {
+        posts::Entity::find()
+            .filter(posts::Column::Slug.is_in(slugs))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|post| post.slug)
+            .collect()
+    };
+
+    let mut reports = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let mut report = ImportEntryReport {
+            file: entry.file,
+            slug: entry.slug,
+            status: ImportStatus::Invalid,
+            post_id: None,
+            errors: Vec::new(),
+        };
+        let request = match entry.request {
+            Ok(request) => request,
+            Err(errors) => {
+                report.errors = errors;
+                reports.push(report);
+                continue;
+            }
+        };
+        if !taken.insert(request.slug.clone()) {
+            report
+                .errors
+                .push(format!("slug: \"{}\" is already in use", request.slug));
+            reports.push(report);
+            continue;
+        }
+        if dry_run {
+            report.status = ImportStatus::Valid;
+        } else {
+            let created = post::create_post(
+                db,
+                CreatePostInput {
+                    title: request.title,
+                    photo_url: request.photo_url,
+                    slug: request.slug,
+                    body: request.body,
+                    published: request.published,
+                    published_at: request.published_at,
+                    visibility: request.visibility,
+                    tags: request.tags,
+                    notify_followers: false,
+                },
+                author_id,
+            )
+            .await;
+            match created {
+                Ok(post) => {
+                    report.status = ImportStatus::Created;
+                    report.post_id = Some(post.id);
+                }
+                Err(err) => {
+                    tracing::error!("Importing {} failed: {}", report.file, err);
+                    report.status = ImportStatus::Failed;
+                    report
+                        .errors
+                        .push("the post could not be saved".to_string());
+                }
+            }
+        }
+        reports.push(report);
+    }
+
+    let count = |status| reports.iter().filter(|r| r.status == status).count();
+    Ok(ImportReport {
+        dry_run,
+        total: reports.len(),
+        valid: count(ImportStatus::Valid) + count(ImportStatus::Created),
+        created: count(ImportStatus::Created),
+        invalid: count(ImportStatus::Invalid),
+        failed: count(ImportStatus::Failed),
+        entries: reports,
+    })
+}
+
+fn render_post(post: &posts::Model, tags: &[tags::Model]) -> String {
+    let front_matter = ExportFrontMatter {
+        title: &post.title,
+        slug: &post.slug,
+        tags: tags.iter().map(|tag| tag.name.as_str()).collect(),
+        published: post.published.unwrap_or(false),
+        date: post
+            .published_at
+            .or(post.created_at)
+            .map(|date| date.with_timezone(&Utc).to_rfc3339())
+            .unwrap_or_default(),
+        visibility: PostVisibility::parse(&post.visibility),
+    };
+    let yaml = serde_yaml::to_string(&front_matter).unwrap_or_default();
+    format!(
+        "---\n{yaml}---\n\n{}\n",
+        post.body.as_deref().unwrap_or_default().trim_end()
+    )
+}
+
+/// Whose posts to export: the caller by id, or any author by current username.
+pub enum ExportAuthor<'a> {
+    Id(Uuid),
+    Username(&'a str),
+}
+
+/// Zips every non-deleted post of `author`, drafts included, as `<slug>.md` files that
+/// `import_posts` reads back. Returns the author's current username with the archive.
+pub async fn export_posts(
+    db: &DatabaseConnection,
+    author: ExportAuthor<'_>,
+) -> Result<(Option<String>, Bytes), ArchiveError> {
+    let query = users::Entity::find().filter(users::Column::DeletedAt.is_null());
+    let query = match author {
+        ExportAuthor::Id(id) => query.filter(users::Column::Id.eq(id)),
+        ExportAuthor::Username(username) => query.filter(users::Column::Username.eq(username)),
+    };
+    let user = query.one(db).await?.ok_or(ArchiveError::UserNotFound)?;
+    let author_posts = posts::Entity::find()
+        .filter(posts::Column::CreatedBy.eq(user.id))
+        .filter(posts::Column::DeletedAt.is_null())
+        .order_by_asc(posts::Column::CreatedAt)
+        .find_with_related(tags::Entity)
+        .all(db)
+        .await?;
+
+    let files: Vec<(String, String)> = author_posts
+        .iter()
+        .map(|(post, post_tags)| (format!("{}.md", post.slug), render_post(post, post_tags)))
+        .collect();
+
+    let archive = tokio::task::spawn_blocking(move || -> Result<Bytes, ArchiveError> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(name, SimpleFileOptions::default())?;
+            zip.write_all(content.as_bytes())
+                .map_err(zip::result::ZipError::Io)?;
+        }
+        Ok(Bytes::from(zip.finish()?.into_inner()))
+    })
+    .await
+    .map_err(|err| ArchiveError::InvalidArchive(err.to_string()))??;
+    Ok((user.username, archive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_front_matter_and_derives_slug_from_file_name() {
+        let entry = parse_entry(
+            "posts/2024-01-05-Hello World.md".to_string(),
+            "---\ntitle: Hello, world\ntags: rust, axum\npublished: true\ndate: 2024-01-05\n---\n\nFirst post body.\n",
+        );
+        let Ok(request) = entry.request else {
+            panic!("entry should be valid");
+        };
+        assert_eq!(request.slug, "hello-world");
+        assert_eq!(request.tags, vec!["rust", "axum"]);
+        assert_eq!(request.body, "First post body.");
+        assert_eq!(request.published_at, parse_date("2024-01-05T00:00:00Z"));
+
+        let missing = parse_entry("draft.md".to_string(), "no front matter here");
+        assert!(missing.request.is_err());
+    }
+}
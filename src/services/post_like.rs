@@ -1,13 +1,16 @@
-use crate::entities::{post_likes, posts};
+use crate::entities::post_likes;
 use crate::models::post_like::{
     LikeStatusResponse, PostLikeListResponse, PostLikeResponse, PostLikeStats,
 };
 use crate::models::user::UserResponse;
+use crate::pagination::{self, Cursor, Page};
+use crate::services::counter::{self, PostCounter};
 use crate::services::user_hydration;
+use crate::services::visibility::{self, PostAccess};
 use chrono::Utc;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    QueryFilter, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
@@ -25,10 +28,13 @@ impl From<DbErr> for PostLikeError {
     }
 }
 
-async fn post_exists(db: &DatabaseConnection, post_id: Uuid) -> Result<bool, DbErr> {
-    Ok(posts::Entity::find_by_id(post_id)
-        .filter(posts::Column::DeletedAt.is_null())
-        .one(db)
+/// Posts `access` may not read are treated as missing.
+async fn post_exists(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    access: &PostAccess<'_>,
+) -> Result<bool, DbErr> {
+    Ok(visibility::find_viewable(db, post_id, access)
         .await?
         .is_some())
 }
@@ -62,8 +68,9 @@ pub async fn like_post(
     db: &DatabaseConnection,
     post_id: Uuid,
     user_id: Uuid,
+    share_token: Option<&str>,
 ) -> Result<(), PostLikeError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, &PostAccess::new(Some(user_id), share_token)).await? {
         return Err(PostLikeError::PostNotFound);
     }
     if like_exists(db, post_id, user_id).await? {
@@ -71,6 +78,7 @@ pub async fn like_post(
     }
 
     let now = Utc::now();
+    let txn = db.begin().await?;
     post_likes::ActiveModel {
         id: Set(Uuid::new_v4()),
         post_id: Set(post_id),
@@ -79,8 +87,10 @@ pub async fn like_post(
         updated_at: Set(Some(now.into())),
         deleted_at: Set(None),
     }
-    .insert(db)
+    .insert(&txn)
     .await?;
+    counter::adjust_post(&txn, post_id, PostCounter::Likes, 1).await?;
+    txn.commit().await?;
 
     Ok(())
 }
@@ -89,21 +99,31 @@ pub async fn unlike_post(
     db: &DatabaseConnection,
     post_id: Uuid,
     user_id: Uuid,
+    share_token: Option<&str>,
 ) -> Result<(), PostLikeError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, &PostAccess::new(Some(user_id), share_token)).await? {
         return Err(PostLikeError::PostNotFound);
     }
 
+    let txn = db.begin().await?;
     let result = post_likes::Entity::delete_many()
         .filter(post_likes::Column::PostId.eq(post_id))
         .filter(post_likes::Column::UserId.eq(user_id))
         .filter(post_likes::Column::DeletedAt.is_null())
-        .exec(db)
+        .exec(&txn)
         .await?;
 
     if result.rows_affected == 0 {
         return Err(PostLikeError::NotLiked);
     }
+    counter::adjust_post(
+        &txn,
+        post_id,
+        PostCounter::Likes,
+        -(result.rows_affected as i64),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(())
 }
@@ -111,10 +131,10 @@ pub async fn unlike_post(
 pub async fn get_likes_by_post_id(
     db: &DatabaseConnection,
     post_id: Uuid,
-    limit: i64,
-    offset: i64,
+    page: &Page,
+    access: &PostAccess<'_>,
 ) -> Result<PostLikeListResponse, PostLikeError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, access).await? {
         return Err(PostLikeError::PostNotFound);
     }
 
@@ -122,33 +142,36 @@ pub async fn get_likes_by_post_id(
         .filter(post_likes::Column::PostId.eq(post_id))
         .filter(post_likes::Column::DeletedAt.is_null());
 
-    let total = query.clone().count(db).await? as i64;
-    let like_models = query
-        .order_by_desc(post_likes::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
+    let paged = pagination::fetch_page(
+        db,
+        query,
+        (post_likes::Column::CreatedAt, post_likes::Column::Id),
+        page,
+        |like| {
+            like.created_at
+                .map(|created_at| Cursor::new(created_at, like.id))
+        },
+    )
+    .await?;
 
-    let users_by_id = load_like_user_map(db, &like_models).await?;
-    let likes = like_models
-        .into_iter()
-        .map(|like| hydrate_like(like, &users_by_id))
-        .collect();
+    let users_by_id = load_like_user_map(db, &paged.items).await?;
+    let paged = paged.map(|like| hydrate_like(like, &users_by_id));
 
     Ok(PostLikeListResponse {
-        likes,
-        total,
-        limit,
-        offset,
+        likes: paged.items,
+        total: paged.total,
+        limit: page.limit(),
+        offset: page.offset(),
+        next_cursor: paged.next_cursor,
     })
 }
 
 pub async fn get_like_stats(
     db: &DatabaseConnection,
     post_id: Uuid,
+    access: &PostAccess<'_>,
 ) -> Result<PostLikeStats, PostLikeError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, access).await? {
         return Err(PostLikeError::PostNotFound);
     }
 
@@ -168,8 +191,9 @@ pub async fn has_user_liked_post(
     db: &DatabaseConnection,
     post_id: Uuid,
     user_id: Uuid,
+    share_token: Option<&str>,
 ) -> Result<LikeStatusResponse, PostLikeError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, &PostAccess::new(Some(user_id), share_token)).await? {
         return Err(PostLikeError::PostNotFound);
     }
 
@@ -0,0 +1,345 @@
+use crate::entities::{post_revisions, posts};
+use crate::models::post::{Post, RenderedBody};
+use crate::models::post_revision::{
+    DiffSegment, FieldDiff, PostRevisionDiffResponse, PostRevisionResponse, PostRevisionSummary,
+};
+use crate::services::markdown;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+    prelude::Expr,
+};
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum PostRevisionError {
+    Db(DbErr),
+    PostNotFound,
+    RevisionNotFound,
+}
+
+impl From<DbErr> for PostRevisionError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum DiffGranularity {
+    Line,
+    Word,
+}
+
+impl DiffGranularity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Word => "word",
+        }
+    }
+}
+
+/// Stores the current title, slug and body of `post` as its next revision. Saves that leave
+/// all three unchanged do not create a revision.
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    post: &posts::Model,
+    editor_id: Uuid,
+) -> Result<(), DbErr> {
+    let latest = latest_revision(conn, post.id).await?;
+    if let Some(latest) = &latest
+        && latest.title == post.title
+        && latest.slug == post.slug
+        && latest.body == post.body
+    {
+        return Ok(());
+    }
+
+    post_revisions::ActiveModel {
+        post_id: Set(post.id),
+        revision_number: Set(latest.map_or(1, |latest| latest.revision_number + 1)),
+        title: Set(post.title.clone()),
+        body: Set(post.body.clone()),
+        slug: Set(post.slug.clone()),
+        created_by: Set(editor_id),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// Stores `post` as its author's first revision when it has none yet, so posts saved before
+/// revisions existed keep their original text once edited.
+pub(crate) async fn record_baseline<C: ConnectionTrait>(
+    conn: &C,
+    post: &posts::Model,
+) -> Result<(), DbErr> {
+    if latest_revision(conn, post.id).await?.is_none() {
+        record(conn, post, post.created_by).await?;
+    }
+    Ok(())
+}
+
+fn cached_render(revision: &post_revisions::Model) -> Option<RenderedBody> {
+    if revision.render_version != Some(markdown::RENDER_VERSION) {
+        return None;
+    }
+    let word_count = revision.word_count?;
+    Some(RenderedBody {
+        html: revision.rendered_html.clone()?,
+        toc: serde_json::from_str(revision.rendered_toc.as_deref()?).ok()?,
+        word_count,
+        reading_time_minutes: markdown::reading_time_minutes(word_count),
+    })
+}
+
+/// Renders `post`'s body, reusing the output cached on its latest revision when that revision
+/// matches the current body. Posts saved before revisions existed are rendered without caching;
+/// reading a post never creates a revision.
+pub async fn rendered_body(
+    db: &DatabaseConnection,
+    post: &posts::Model,
+) -> Result<RenderedBody, DbErr> {
+    let latest = latest_revision(db, post.id).await?;
+    render_with_cache(db, post, latest).await
+}
+
+/// `rendered_body` for a list of posts, loading their latest revisions in one query.
+pub async fn rendered_bodies(
+    db: &DatabaseConnection,
+    posts: &[posts::Model],
+) -> Result<HashMap<Uuid, RenderedBody>, DbErr> {
+    if posts.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut latest: HashMap<Uuid, post_revisions::Model> = post_revisions::Entity::find()
+        .filter(post_revisions::Column::PostId.is_in(posts.iter().map(|post| post.id)))
+        .filter(Expr::cust(
+            "post_revisions.revision_number = (SELECT MAX(r.revision_number) \
+             FROM post_revisions r WHERE r.post_id = post_revisions.post_id)",
+        ))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|revision| (revision.post_id, revision))
+        .collect();
+
+    let mut rendered = HashMap::with_capacity(posts.len());
+    for post in posts {
+        let body = render_with_cache(db, post, latest.remove(&post.id)).await?;
+        rendered.insert(post.id, body);
+    }
+    Ok(rendered)
+}
+
+/// Renders `post`, reading and filling the cache on `latest` when it holds the current body.
+async fn render_with_cache(
+    db: &DatabaseConnection,
+    post: &posts::Model,
+    latest: Option<post_revisions::Model>,
+) -> Result<RenderedBody, DbErr> {
+    let Some(latest) = latest.filter(|latest| latest.body == post.body) else {
+        return Ok(markdown::render(post.body.as_deref().unwrap_or_default()));
+    };
+    if let Some(rendered) = cached_render(&latest) {
+        return Ok(rendered);
+    }
+
+    let rendered = markdown::render(latest.body.as_deref().unwrap_or_default());
+    let mut active = latest.into_active_model();
+    active.rendered_html = Set(Some(rendered.html.clone()));
+    active.rendered_toc = Set(serde_json::to_string(&rendered.toc).ok());
+    active.word_count = Set(Some(rendered.word_count));
+    active.render_version = Set(Some(markdown::RENDER_VERSION));
+    active.update(db).await?;
+    Ok(rendered)
+}
+
+async fn latest_revision<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+) -> Result<Option<post_revisions::Model>, DbErr> {
+    post_revisions::Entity::find()
+        .filter(post_revisions::Column::PostId.eq(post_id))
+        .order_by_desc(post_revisions::Column::RevisionNumber)
+        .one(conn)
+        .await
+}
+
+async fn find_revision(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    revision_id: i64,
+) -> Result<post_revisions::Model, PostRevisionError> {
+    post_revisions::Entity::find_by_id(revision_id)
+        .filter(post_revisions::Column::PostId.eq(post_id))
+        .one(db)
+        .await?
+        .ok_or(PostRevisionError::RevisionNotFound)
+}
+
+pub async fn list_revisions(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    offset: i64,
+    limit: i64,
+) -> Result<(Vec<PostRevisionSummary>, i64), DbErr> {
+    let query = post_revisions::Entity::find().filter(post_revisions::Column::PostId.eq(post_id));
+
+    let total = query.clone().count(db).await? as i64;
+    let revisions = query
+        .order_by_desc(post_revisions::Column::RevisionNumber)
+        .limit(limit.max(0) as u64)
+        .offset(offset.max(0) as u64)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok((revisions, total))
+}
+
+pub async fn get_revision(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    revision_id: i64,
+) -> Result<PostRevisionResponse, PostRevisionError> {
+    Ok(find_revision(db, post_id, revision_id).await?.into())
+}
+
+fn diff_text(old: &str, new: &str, granularity: DiffGranularity) -> FieldDiff {
+    let diff = match granularity {
+        DiffGranularity::Line => TextDiff::from_lines(old, new),
+        DiffGranularity::Word => TextDiff::from_words(old, new),
+    };
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let (mut insertions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        let op = match change.tag() {
+            ChangeTag::Equal => "equal",
+            ChangeTag::Insert => {
+                insertions += 1;
+                "insert"
+            }
+            ChangeTag::Delete => {
+                deletions += 1;
+                "delete"
+            }
+        };
+        match segments.last_mut() {
+            Some(last) if last.op == op => last.value.push_str(change.value()),
+            _ => segments.push(DiffSegment {
+                op,
+                value: change.value().to_string(),
+            }),
+        }
+    }
+
+    FieldDiff {
+        changed: insertions > 0 || deletions > 0,
+        insertions,
+        deletions,
+        segments,
+    }
+}
+
+pub async fn diff_revisions(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    from_id: i64,
+    to_id: i64,
+    granularity: DiffGranularity,
+) -> Result<PostRevisionDiffResponse, PostRevisionError> {
+    let from = find_revision(db, post_id, from_id).await?;
+    let to = find_revision(db, post_id, to_id).await?;
+
+    let title = diff_text(&from.title, &to.title, DiffGranularity::Word);
+    let slug = diff_text(&from.slug, &to.slug, DiffGranularity::Word);
+    let body = diff_text(
+        from.body.as_deref().unwrap_or_default(),
+        to.body.as_deref().unwrap_or_default(),
+        granularity,
+    );
+
+    Ok(PostRevisionDiffResponse {
+        post_id,
+        from: from.into(),
+        to: to.into(),
+        granularity: granularity.as_str(),
+        title,
+        slug,
+        body,
+    })
+}
+
+/// Copies a revision's title, slug and body back onto the post. The restored state is itself
+/// recorded as a new revision, so a restore can be undone.
+pub async fn restore_revision(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    revision_id: i64,
+    editor_id: Uuid,
+) -> Result<Post, PostRevisionError> {
+    let revision = find_revision(db, post_id, revision_id).await?;
+    let Some(post) = posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+    else {
+        return Err(PostRevisionError::PostNotFound);
+    };
+
+    let txn = db.begin().await?;
+    let old_slug = post.slug.clone();
+    let version = post.version;
+    let mut active = post.into_active_model();
+    active.title = Set(revision.title);
+    active.body = Set(revision.body);
+    active.slug = Set(revision.slug);
+    active.updated_at = Set(Some(Utc::now().into()));
+    active.version = Set(version + 1);
+    let post = active.update(&txn).await?;
+    crate::services::slug_history::record_post_slug(&txn, post.id, &old_slug, &post.slug).await?;
+    record(&txn, &post, editor_id).await?;
+    txn.commit().await?;
+
+    crate::services::post::get_post_by_id(
+        db,
+        post.id,
+        false,
+        &crate::services::visibility::PostAccess::new(Some(editor_id), None),
+    )
+    .await?
+    .ok_or(PostRevisionError::PostNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_text_merges_consecutive_changes_into_segments() {
+        let diff = diff_text(
+            "the quick fox jumps",
+            "the slow fox jumps",
+            DiffGranularity::Word,
+        );
+
+        assert!(diff.changed);
+        let ops: Vec<&str> = diff.segments.iter().map(|segment| segment.op).collect();
+        assert_eq!(ops, vec!["equal", "delete", "insert", "equal"]);
+        assert_eq!(diff.segments[1].value, "quick");
+        assert_eq!(diff.segments[2].value, "slow");
+        assert_eq!(diff.segments[3].value, " fox jumps");
+
+        let unchanged = diff_text("same\n", "same\n", DiffGranularity::Line);
+        assert!(!unchanged.changed);
+    }
+}
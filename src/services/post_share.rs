@@ -0,0 +1,98 @@
+use crate::config::SiteConfig;
+use crate::entities::{post_share_tokens, posts};
+use crate::models::post_share::ShareTokenResponse;
+use crate::services::visibility::hash_share_token;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use rand::RngCore;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum PostShareError {
+    Db(DbErr),
+    PostNotFound,
+    TokenNotFound,
+}
+
+impl From<DbErr> for PostShareError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+async fn ensure_post(db: &DatabaseConnection, post_id: Uuid) -> Result<(), PostShareError> {
+    posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .map(|_| ())
+        .ok_or(PostShareError::PostNotFound)
+}
+
+/// Issues a new secret link for the post. The token is returned once and cannot be read back.
+pub async fn create_share_token(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    user_id: Uuid,
+) -> Result<ShareTokenResponse, PostShareError> {
+    ensure_post(db, post_id).await?;
+
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let token = URL_SAFE_NO_PAD.encode(secret);
+    let model = post_share_tokens::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        post_id: Set(post_id),
+        token_hash: Set(hash_share_token(&token)),
+        created_by: Set(user_id),
+        created_at: Set(Utc::now().into()),
+        revoked_at: Set(None),
+    }
+    .insert(db)
+    .await?;
+
+    let mut response = ShareTokenResponse::from_entity(model);
+    response.url =
+        Some(SiteConfig::get().url(&format!("/api/posts/{post_id}?share_token={token}")));
+    response.token = Some(token);
+    Ok(response)
+}
+
+pub async fn list_share_tokens(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+) -> Result<Vec<ShareTokenResponse>, PostShareError> {
+    ensure_post(db, post_id).await?;
+    Ok(post_share_tokens::Entity::find()
+        .filter(post_share_tokens::Column::PostId.eq(post_id))
+        .order_by_desc(post_share_tokens::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(ShareTokenResponse::from_entity)
+        .collect())
+}
+
+/// Revoking is permanent; links using the token stop working immediately.
+pub async fn revoke_share_token(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    token_id: Uuid,
+) -> Result<ShareTokenResponse, PostShareError> {
+    let token = post_share_tokens::Entity::find_by_id(token_id)
+        .filter(post_share_tokens::Column::PostId.eq(post_id))
+        .one(db)
+        .await?
+        .ok_or(PostShareError::TokenNotFound)?;
+    if token.revoked_at.is_some() {
+        return Ok(ShareTokenResponse::from_entity(token));
+    }
+
+    let mut active = token.into_active_model();
+    active.revoked_at = Set(Some(Utc::now().into()));
+    Ok(ShareTokenResponse::from_entity(active.update(db).await?))
+}
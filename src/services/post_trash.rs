@@ -0,0 +1,208 @@
+//! Soft-deleted posts stay in their author's trash until restored, purged by hand, or removed
+//! by the purge job once the retention period has passed.
+
+use crate::entities::{
+    comment_moderation_log, comment_reactions, post_bookmarks, post_comments, post_likes,
+    post_revisions, post_share_tokens, post_slug_history, post_trending_scores, post_views, posts,
+    posts_to_tags, series_posts,
+};
+use crate::models::post::Post;
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::post::hydrate_posts;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+/// Posts hard-deleted per purge transaction, so one run never holds locks for long.
+const PURGE_BATCH_SIZE: u64 = 100;
+
+#[derive(Debug)]
+pub enum TrashError {
+    Db(DbErr),
+    PostNotFound,
+    NotOwner,
+}
+
+impl From<DbErr> for TrashError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+/// The author's trashed posts, most recently deleted first.
+pub async fn list_trash(
+    db: &DatabaseConnection,
+    author_id: Uuid,
+    page: &Page,
+) -> Result<Paged<Post>, DbErr> {
+    let query = posts::Entity::find()
+        .filter(posts::Column::CreatedBy.eq(author_id))
+        .filter(posts::Column::DeletedAt.is_not_null());
+    let Paged {
+        items,
+        total,
+        next_cursor,
+    } = pagination::fetch_page(
+        db,
+        query,
+        (posts::Column::DeletedAt, posts::Column::Id),
+        page,
+        |post| {
+            post.deleted_at
+                .map(|deleted_at| Cursor::new(deleted_at, post.id))
+        },
+    )
+    .await?;
+    Ok(Paged {
+        items: hydrate_posts(db, items, true).await?,
+        total,
+        next_cursor,
+    })
+}
+
+async fn find_trashed(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    author_id: Uuid,
+) -> Result<posts::Model, TrashError> {
+    let post = posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_not_null())
+        .one(db)
+        .await?
+        .ok_or(TrashError::PostNotFound)?;
+    if post.created_by != author_id {
+        return Err(TrashError::NotOwner);
+    }
+    Ok(post)
+}
+
+/// Moves a post out of the trash with its comments, likes and tags intact.
+pub async fn restore_post(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    author_id: Uuid,
+) -> Result<Post, TrashError> {
+    let post = find_trashed(db, post_id, author_id).await?;
+    let version = post.version;
+    let mut active = post.into_active_model();
+    active.deleted_at = Set(None);
+    active.updated_at = Set(Some(Utc::now().into()));
+    active.version = Set(version + 1);
+    let post = active.update(db).await?;
+    hydrate_posts(db, vec![post], false)
+        .await?
+        .pop()
+        .ok_or(TrashError::PostNotFound)
+}
+
+/// Permanently deletes a trashed post and everything hanging off it.
+pub async fn purge_post(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    author_id: Uuid,
+) -> Result<(), TrashError> {
+    let post = find_trashed(db, post_id, author_id).await?;
+    let txn = db.begin().await?;
+    delete_posts(&txn, &[post.id]).await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Hard-deletes posts that have been in the trash longer than `retention`. Returns how many
+/// were removed.
+pub async fn purge_expired(db: &DatabaseConnection, retention: Duration) -> Result<u64, DbErr> {
+    let cutoff = Utc::now() - retention;
+    let mut purged = 0;
+    loop {
+        let txn = db.begin().await?;
+        let ids: Vec<Uuid> = posts::Entity::find()
+            .select_only()
+            .column(posts::Column::Id)
+            .filter(posts::Column::DeletedAt.lt(cutoff))
+            .order_by_asc(posts::Column::DeletedAt)
+            .limit(PURGE_BATCH_SIZE)
+            .lock_with_behavior(
+                sea_orm::sea_query::LockType::Update,
+                sea_orm::sea_query::LockBehavior::SkipLocked,
+            )
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if ids.is_empty() {
+            txn.commit().await?;
+            return Ok(purged);
+        }
+        delete_posts(&txn, &ids).await?;
+        txn.commit().await?;
+        purged += ids.len() as u64;
+    }
+}
+
+/// Deletes the posts and every row referencing them.
+async fn delete_posts<C: ConnectionTrait>(conn: &C, ids: &[Uuid]) -> Result<(), DbErr> {
+    let ids = ids.to_vec();
+    post_likes::Entity::delete_many()
+        .filter(post_likes::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_views::Entity::delete_many()
+        .filter(post_views::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    comment_moderation_log::Entity::delete_many()
+        .filter(comment_moderation_log::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    comment_reactions::Entity::delete_many()
+        .filter(
+            comment_reactions::Column::CommentId.in_subquery(
+                post_comments::Entity::find()
+                    .select_only()
+                    .column(post_comments::Column::Id)
+                    .filter(post_comments::Column::PostId.is_in(ids.clone()))
+                    .into_query(),
+            ),
+        )
+        .exec(conn)
+        .await?;
+    post_comments::Entity::delete_many()
+        .filter(post_comments::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_bookmarks::Entity::delete_many()
+        .filter(post_bookmarks::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    posts_to_tags::Entity::delete_many()
+        .filter(posts_to_tags::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_revisions::Entity::delete_many()
+        .filter(post_revisions::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_share_tokens::Entity::delete_many()
+        .filter(post_share_tokens::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_slug_history::Entity::delete_many()
+        .filter(post_slug_history::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    post_trending_scores::Entity::delete_many()
+        .filter(post_trending_scores::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    series_posts::Entity::delete_many()
+        .filter(series_posts::Column::PostId.is_in(ids.clone()))
+        .exec(conn)
+        .await?;
+    posts::Entity::delete_many()
+        .filter(posts::Column::Id.is_in(ids))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
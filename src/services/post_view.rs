@@ -1,13 +1,20 @@
-use crate::entities::{post_views, posts};
+use crate::config::ViewConfig;
+use crate::entities::post_views;
 use crate::models::post_view::{PostViewResponse, PostViewStats, ViewStatusResponse};
 use crate::models::user::UserResponse;
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::counter::{self, PostCounter};
 use crate::services::user_hydration;
+use crate::services::visibility::{self, PostAccess};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend,
+    DbErr, EntityTrait, QueryFilter, Set, Statement, TransactionTrait,
 };
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
+use std::net::IpAddr;
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -22,10 +29,93 @@ impl From<DbErr> for PostViewError {
     }
 }
 
-async fn post_exists(db: &DatabaseConnection, post_id: Uuid) -> Result<bool, DbErr> {
-    Ok(posts::Entity::find_by_id(post_id)
-        .filter(posts::Column::DeletedAt.is_null())
-        .one(db)
+/// Posts `access` may not read are treated as missing.
+async fn post_exists(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+    access: &PostAccess<'_>,
+) -> Result<bool, DbErr> {
+    Ok(visibility::find_viewable(db, post_id, access)
+        .await?
+        .is_some())
+}
+
+/// Lowercase user-agent fragments of crawlers, link unfurlers and scripted clients.
+const BOT_USER_AGENT_MARKERS: &[&str] = &[
+    "bot",
+    "crawl",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "embedly",
+    "preview",
+    "headless",
+    "lighthouse",
+    "curl/",
+    "wget/",
+    "python-requests",
+    "go-http-client",
+    "okhttp",
+];
+
+pub fn is_bot_user_agent(user_agent: Option<&str>) -> bool {
+    let Some(user_agent) = user_agent else {
+        return true;
+    };
+    let user_agent = user_agent.to_ascii_lowercase();
+    BOT_USER_AGENT_MARKERS
+        .iter()
+        .any(|marker| user_agent.contains(marker))
+}
+
+fn fingerprint(ip_address: Option<&str>, user_agent: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(ip_address.unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(user_agent.unwrap_or_default());
+    URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
+/// Views that count towards `view_count`: not from a bot and not a repeat.
+pub(crate) fn is_counted() -> Condition {
+    Condition::all()
+        .add(post_views::Column::DeletedAt.is_null())
+        .add(post_views::Column::IsBot.eq(false))
+        .add(post_views::Column::IsDuplicate.eq(false))
+}
+
+/// SQL twin of `is_counted` for raw queries that alias `post_views` as `v`.
+pub(crate) const COUNTED_VIEW_SQL: &str =
+    "v.deleted_at IS NULL AND v.is_bot = false AND v.is_duplicate = false";
+
+/// The viewer's address: the `X-Forwarded-For` entry added by the outermost of
+/// `trusted_hops` proxies, counted from the right since clients can prepend anything. Falls
+/// back to the connecting address when there are no trusted proxies or too few entries.
+pub fn client_ip(forwarded_for: Option<&str>, peer: IpAddr, trusted_hops: usize) -> String {
+    if trusted_hops == 0 {
+        return peer.to_string();
+    }
+    forwarded_for
+        .and_then(|value| value.split(',').rev().nth(trusted_hops - 1))
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map_or_else(|| peer.to_string(), ToOwned::to_owned)
+}
+
+async fn seen_fingerprint_recently<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    fingerprint: &str,
+) -> Result<bool, DbErr> {
+    let window = chrono::Duration::from_std(ViewConfig::get().dedup_window)
+        .unwrap_or_else(|_| chrono::Duration::zero());
+    Ok(post_views::Entity::find()
+        .filter(post_views::Column::PostId.eq(post_id))
+        .filter(post_views::Column::Fingerprint.eq(fingerprint))
+        .filter(post_views::Column::UserId.is_null())
+        .filter(is_counted())
+        .filter(post_views::Column::CreatedAt.gte(Utc::now() - window))
+        .one(conn)
         .await?
         .is_some())
 }
@@ -67,8 +157,9 @@ pub async fn record_view(
     user_id: Option<Uuid>,
     ip_address: Option<String>,
     user_agent: Option<String>,
+    share_token: Option<&str>,
 ) -> Result<(), PostViewError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, &PostAccess::new(user_id, share_token)).await? {
         return Err(PostViewError::PostNotFound);
     }
 
@@ -78,19 +169,48 @@ pub async fn record_view(
         return Ok(());
     }
 
+    let ip_address = ip_address.filter(|value| !value.trim().is_empty());
+    let user_agent = user_agent.filter(|value| !value.trim().is_empty());
+    let is_bot = user_id.is_none() && is_bot_user_agent(user_agent.as_deref());
+    let fingerprint = user_id
+        .is_none()
+        .then(|| fingerprint(ip_address.as_deref(), user_agent.as_deref()));
+
+    let txn = db.begin().await?;
+    let is_duplicate = match &fingerprint {
+        Some(fingerprint) if !is_bot => {
+            // Serializes concurrent views with the same fingerprint until this one commits.
+            txn.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+                [format!("post_view:{post_id}:{fingerprint}").into()],
+            ))
+            .await?;
+            seen_fingerprint_recently(&txn, post_id, fingerprint).await?
+        }
+        _ => false,
+    };
+
     let now = Utc::now();
     post_views::ActiveModel {
         id: Set(Uuid::new_v4()),
         post_id: Set(post_id),
         user_id: Set(user_id),
-        ip_address: Set(ip_address.filter(|value| !value.trim().is_empty())),
-        user_agent: Set(user_agent.filter(|value| !value.trim().is_empty())),
+        ip_address: Set(ip_address),
+        user_agent: Set(user_agent),
+        fingerprint: Set(fingerprint),
+        is_bot: Set(is_bot),
+        is_duplicate: Set(is_duplicate),
         created_at: Set(Some(now.into())),
         updated_at: Set(Some(now.into())),
         deleted_at: Set(None),
     }
-    .insert(db)
+    .insert(&txn)
     .await?;
+    if !is_bot && !is_duplicate {
+        counter::adjust_post(&txn, post_id, PostCounter::Views, 1).await?;
+    }
+    txn.commit().await?;
 
     Ok(())
 }
@@ -98,63 +218,80 @@ pub async fn record_view(
 pub async fn get_views_by_post_id(
     db: &DatabaseConnection,
     post_id: Uuid,
-    limit: i64,
-    offset: i64,
-) -> Result<(Vec<PostViewResponse>, i64), PostViewError> {
-    if !post_exists(db, post_id).await? {
+    page: &Page,
+    access: &PostAccess<'_>,
+) -> Result<Paged<PostViewResponse>, PostViewError> {
+    if !post_exists(db, post_id, access).await? {
         return Err(PostViewError::PostNotFound);
     }
 
     let query = post_views::Entity::find()
         .filter(post_views::Column::PostId.eq(post_id))
-        .filter(post_views::Column::DeletedAt.is_null());
+        .filter(is_counted());
 
-    let total = query.clone().count(db).await? as i64;
-    let view_models = query
-        .order_by_desc(post_views::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-
-    let users_by_id = load_view_user_map(db, &view_models).await?;
-    let views = view_models
-        .into_iter()
-        .map(|view| hydrate_view(view, &users_by_id))
-        .collect();
+    let views = pagination::fetch_page(
+        db,
+        query,
+        (post_views::Column::CreatedAt, post_views::Column::Id),
+        page,
+        |view| {
+            view.created_at
+                .map(|created_at| Cursor::new(created_at, view.id))
+        },
+    )
+    .await?;
 
-    Ok((views, total))
+    let users_by_id = load_view_user_map(db, &views.items).await?;
+    Ok(views.map(|view| hydrate_view(view, &users_by_id)))
 }
 
 pub async fn get_view_stats(
     db: &DatabaseConnection,
     post_id: Uuid,
+    access: &PostAccess<'_>,
 ) -> Result<PostViewStats, PostViewError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, access).await? {
         return Err(PostViewError::PostNotFound);
     }
 
-    let views = post_views::Entity::find()
+    let all_views = post_views::Entity::find()
         .filter(post_views::Column::PostId.eq(post_id))
         .filter(post_views::Column::DeletedAt.is_null())
         .all(db)
         .await?;
 
+    let unfiltered_views = all_views.len() as i64;
+    let bot_views = all_views.iter().filter(|view| view.is_bot).count() as i64;
+    let duplicate_views = all_views.iter().filter(|view| view.is_duplicate).count() as i64;
+    let views: Vec<_> = all_views
+        .iter()
+        .filter(|view| !view.is_bot && !view.is_duplicate)
+        .collect();
+
     let total_views = views.len() as i64;
     let authenticated_views = views.iter().filter(|view| view.user_id.is_some()).count() as i64;
     let anonymous_views = total_views - authenticated_views;
-    let unique_views = views
+    let unique_users = views
         .iter()
         .filter_map(|view| view.user_id)
         .collect::<HashSet<_>>()
-        .len() as i64;
+        .len();
+    let unique_visitors = views
+        .iter()
+        .filter(|view| view.user_id.is_none())
+        .filter_map(|view| view.fingerprint.as_deref())
+        .collect::<HashSet<_>>()
+        .len();
 
     Ok(PostViewStats {
         post_id,
         total_views,
-        unique_views,
+        unique_views: (unique_users + unique_visitors) as i64,
         anonymous_views,
         authenticated_views,
+        unfiltered_views,
+        bot_views,
+        duplicate_views,
     })
 }
 
@@ -162,8 +299,9 @@ pub async fn has_user_viewed_post(
     db: &DatabaseConnection,
     post_id: Uuid,
     user_id: Uuid,
+    share_token: Option<&str>,
 ) -> Result<ViewStatusResponse, PostViewError> {
-    if !post_exists(db, post_id).await? {
+    if !post_exists(db, post_id, &PostAccess::new(Some(user_id), share_token)).await? {
         return Err(PostViewError::PostNotFound);
     }
 
@@ -171,3 +309,32 @@ pub async fn has_user_viewed_post(
         has_viewed: has_user_viewed(db, post_id, user_id).await?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{client_ip, is_bot_user_agent};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn is_bot_user_agent_flags_crawlers_and_missing_agents() {
+        assert!(is_bot_user_agent(None));
+        assert!(is_bot_user_agent(Some(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)"
+        )));
+        assert!(is_bot_user_agent(Some("curl/8.4.0")));
+        assert!(!is_bot_user_agent(Some(
+            "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0"
+        )));
+    }
+
+    #[test]
+    fn client_ip_only_trusts_entries_added_by_known_proxies() {
+        let peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let forwarded = Some("6.6.6.6, 203.0.113.7, 10.0.0.2");
+        assert_eq!(client_ip(forwarded, peer, 0), "10.0.0.1");
+        assert_eq!(client_ip(forwarded, peer, 1), "10.0.0.2");
+        assert_eq!(client_ip(forwarded, peer, 2), "203.0.113.7");
+        assert_eq!(client_ip(Some("203.0.113.7"), peer, 2), "10.0.0.1");
+        assert_eq!(client_ip(None, peer, 1), "10.0.0.1");
+    }
+}
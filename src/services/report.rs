@@ -1,5 +1,6 @@
 use crate::entities::{post_comments, post_likes, post_views, posts, users};
 use crate::models::report::*;
+use crate::services::post_view;
 use chrono::{Duration, NaiveDate, Utc};
 use sea_orm::{
     ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
@@ -40,7 +41,10 @@ pub async fn overview(db: &DatabaseConnection) -> Result<OverviewStatsResponse,
         .filter(posts::Column::Published.eq(true))
         .count(db)
         .await? as i64;
-    let total_views = post_views::Entity::find().count(db).await? as i64;
+    let total_views = post_views::Entity::find()
+        .filter(post_view::is_counted())
+        .count(db)
+        .await? as i64;
     let total_likes = post_likes::Entity::find().count(db).await? as i64;
     let total_comments = post_comments::Entity::find().count(db).await? as i64;
     let new_users_today = scalar_i64(
@@ -0,0 +1,369 @@
+use crate::entities::{posts, series, series_posts, users};
+use crate::models::series::{PostSeriesNav, SeriesPostItem, SeriesPostLink, SeriesResponse};
+use crate::services::visibility::PostVisibility;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait, sea_query::Expr,
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum SeriesError {
+    Db(DbErr),
+    NotFound,
+    PostNotFound,
+    PostAlreadyInSeries,
+    PostNotInSeries,
+    InvalidOrder,
+}
+
+impl From<DbErr> for SeriesError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+/// Live and public, matching `post::is_listed`.
+fn is_live(post: &posts::Model) -> bool {
+    post.visibility == PostVisibility::Public.as_str()
+        && post.published.unwrap_or(false)
+        && post.deleted_at.is_none()
+        && post
+            .published_at
+            .is_none_or(|published_at| published_at <= Utc::now())
+}
+
+/// Series members in order. Without `include_unpublished` only live posts are returned,
+/// which is what readers see.
+async fn series_posts<C: ConnectionTrait>(
+    conn: &C,
+    series_id: Uuid,
+    include_unpublished: bool,
+) -> Result<Vec<(series_posts::Model, posts::Model)>, DbErr> {
+    Ok(series_posts::Entity::find()
+        .filter(series_posts::Column::SeriesId.eq(series_id))
+        .find_also_related(posts::Entity)
+        .order_by_asc(series_posts::Column::Position)
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter_map(|(member, post)| post.map(|post| (member, post)))
+        .filter(|(_, post)| post.deleted_at.is_none() && (include_unpublished || is_live(post)))
+        .collect())
+}
+
+async fn series_response(
+    db: &DatabaseConnection,
+    series: series::Model,
+    include_unpublished: bool,
+) -> Result<SeriesResponse, DbErr> {
+    let username = series
+        .find_related(users::Entity)
+        .one(db)
+        .await?
+        .and_then(|user| user.username);
+    let posts = series_posts(db, series.id, include_unpublished)
+        .await?
+        .into_iter()
+        .map(|(member, post)| SeriesPostItem {
+            published: is_live(&post),
+            id: post.id,
+            title: post.title,
+            slug: post.slug,
+            position: member.position,
+        })
+        .collect();
+    Ok(SeriesResponse::from_entity(series, username, posts))
+}
+
+async fn find_owned_series<C: ConnectionTrait>(
+    conn: &C,
+    series_id: Uuid,
+    user_id: Uuid,
+) -> Result<series::Model, SeriesError> {
+    series::Entity::find_by_id(series_id)
+        .filter(series::Column::UserId.eq(user_id))
+        .one(conn)
+        .await?
+        .ok_or(SeriesError::NotFound)
+}
+
+/// Rewrites positions as 1..n in the given order of visible members. Members left out, such
+/// as posts in the trash, keep their relative order after them so no two share a position.
+async fn write_positions<C: ConnectionTrait>(
+    conn: &C,
+    series_id: Uuid,
+    post_ids: &[Uuid],
+) -> Result<(), DbErr> {
+    let hidden: Vec<Uuid> = series_posts::Entity::find()
+        .filter(series_posts::Column::SeriesId.eq(series_id))
+        .filter(series_posts::Column::PostId.is_not_in(post_ids.to_vec()))
+        .order_by_asc(series_posts::Column::Position)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|member| member.post_id)
+        .collect();
+    for (index, post_id) in post_ids.iter().chain(&hidden).enumerate() {
+        series_posts::Entity::update_many()
+            .col_expr(
+                series_posts::Column::Position,
+                Expr::value(index as i32 + 1),
+            )
+            .filter(series_posts::Column::SeriesId.eq(series_id))
+            .filter(series_posts::Column::PostId.eq(*post_id))
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn create_series(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    title: String,
+    description: Option<String>,
+) -> Result<SeriesResponse, SeriesError> {
+    let now = Utc::now();
+    let series = series::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        title: Set(title),
+        description: Set(description),
+        created_at: Set(Some(now.into())),
+        updated_at: Set(Some(now.into())),
+    }
+    .insert(db)
+    .await?;
+    Ok(series_response(db, series, true).await?)
+}
+
+/// Owners also see their unpublished and scheduled posts in the list.
+pub async fn get_series(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    viewer_id: Option<Uuid>,
+) -> Result<SeriesResponse, SeriesError> {
+    let Some(series) = series::Entity::find_by_id(series_id).one(db).await? else {
+        return Err(SeriesError::NotFound);
+    };
+    let is_owner = viewer_id == Some(series.user_id);
+    Ok(series_response(db, series, is_owner).await?)
+}
+
+pub async fn get_series_by_username(
+    db: &DatabaseConnection,
+    username: &str,
+) -> Result<Vec<SeriesResponse>, SeriesError> {
+    let Some(user) = users::Entity::find()
+        .filter(users::Column::Username.eq(username))
+        .filter(users::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let series = user
+        .find_related(series::Entity)
+        .order_by_desc(series::Column::CreatedAt)
+        .all(db)
+        .await?;
+    let mut out = Vec::with_capacity(series.len());
+    for item in series {
+        out.push(series_response(db, item, false).await?);
+    }
+    Ok(out)
+}
+
+pub async fn update_series(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    user_id: Uuid,
+    title: Option<String>,
+    description: Option<String>,
+) -> Result<SeriesResponse, SeriesError> {
+    let series = find_owned_series(db, series_id, user_id).await?;
+    let mut active = series.into_active_model();
+    if let Some(title) = title {
+        active.title = Set(title);
+    }
+    if description.is_some() {
+        active.description = Set(description);
+    }
+    active.updated_at = Set(Some(Utc::now().into()));
+    let updated = active.update(db).await?;
+    Ok(series_response(db, updated, true).await?)
+}
+
+/// Deletes the series only; its posts stay as they are.
+pub async fn delete_series(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), SeriesError> {
+    let series = find_owned_series(db, series_id, user_id).await?;
+    let txn = db.begin().await?;
+    series_posts::Entity::delete_many()
+        .filter(series_posts::Column::SeriesId.eq(series.id))
+        .exec(&txn)
+        .await?;
+    series.delete(&txn).await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Adds one of the owner's posts at `position` (1-based), or at the end when omitted.
+pub async fn add_post(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    user_id: Uuid,
+    post_id: Uuid,
+    position: Option<i32>,
+) -> Result<SeriesResponse, SeriesError> {
+    let series = find_owned_series(db, series_id, user_id).await?;
+    let Some(_) = posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::CreatedBy.eq(user_id))
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+    else {
+        return Err(SeriesError::PostNotFound);
+    };
+    if series_posts::Entity::find()
+        .filter(series_posts::Column::PostId.eq(post_id))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Err(SeriesError::PostAlreadyInSeries);
+    }
+
+    let txn = db.begin().await?;
+    let mut order: Vec<Uuid> = series_posts(&txn, series.id, true)
+        .await?
+        .into_iter()
+        .map(|(member, _)| member.post_id)
+        .collect();
+    let index = position
+        .map(|position| (position.max(1) as usize - 1).min(order.len()))
+        .unwrap_or(order.len());
+    order.insert(index, post_id);
+
+    series_posts::ActiveModel {
+        series_id: Set(series.id),
+        post_id: Set(post_id),
+        position: Set(index as i32 + 1),
+    }
+    .insert(&txn)
+    .await?;
+    write_positions(&txn, series.id, &order).await?;
+    txn.commit().await?;
+
+    Ok(series_response(db, series, true).await?)
+}
+
+pub async fn remove_post(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    user_id: Uuid,
+    post_id: Uuid,
+) -> Result<SeriesResponse, SeriesError> {
+    let series = find_owned_series(db, series_id, user_id).await?;
+    let txn = db.begin().await?;
+    let result = series_posts::Entity::delete_many()
+        .filter(series_posts::Column::SeriesId.eq(series.id))
+        .filter(series_posts::Column::PostId.eq(post_id))
+        .exec(&txn)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(SeriesError::PostNotInSeries);
+    }
+    let order: Vec<Uuid> = series_posts(&txn, series.id, true)
+        .await?
+        .into_iter()
+        .map(|(member, _)| member.post_id)
+        .collect();
+    write_positions(&txn, series.id, &order).await?;
+    txn.commit().await?;
+
+    Ok(series_response(db, series, true).await?)
+}
+
+/// `post_ids` must list every post the owner sees in the series exactly once; trashed posts
+/// are left out and stay after them.
+pub async fn reorder_posts(
+    db: &DatabaseConnection,
+    series_id: Uuid,
+    user_id: Uuid,
+    post_ids: Vec<Uuid>,
+) -> Result<SeriesResponse, SeriesError> {
+    let series = find_owned_series(db, series_id, user_id).await?;
+    let txn = db.begin().await?;
+    let current: HashSet<Uuid> = series_posts(&txn, series.id, true)
+        .await?
+        .into_iter()
+        .map(|(member, _)| member.post_id)
+        .collect();
+    let requested: HashSet<Uuid> = post_ids.iter().copied().collect();
+    if requested.len() != post_ids.len() || requested != current {
+        return Err(SeriesError::InvalidOrder);
+    }
+    write_positions(&txn, series.id, &post_ids).await?;
+    txn.commit().await?;
+
+    Ok(series_response(db, series, true).await?)
+}
+
+fn link(post: &posts::Model) -> SeriesPostLink {
+    SeriesPostLink {
+        id: post.id,
+        title: post.title.clone(),
+        slug: post.slug.clone(),
+    }
+}
+
+/// Previous/next navigation for a post. Only live posts are linked, so readers never land
+/// on a draft; the post itself is always counted.
+pub async fn post_navigation(
+    db: &DatabaseConnection,
+    post_id: Uuid,
+) -> Result<Option<PostSeriesNav>, DbErr> {
+    let Some(membership) = series_posts::Entity::find()
+        .filter(series_posts::Column::PostId.eq(post_id))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let Some(series) = series::Entity::find_by_id(membership.series_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let members: Vec<posts::Model> = series_posts(db, series.id, true)
+        .await?
+        .into_iter()
+        .map(|(_, post)| post)
+        .filter(|post| post.id == post_id || is_live(post))
+        .collect();
+    let Some(index) = members.iter().position(|post| post.id == post_id) else {
+        return Ok(None);
+    };
+
+    Ok(Some(PostSeriesNav {
+        id: series.id,
+        title: series.title,
+        position: index as i32 + 1,
+        total: members.len() as i64,
+        previous: index
+            .checked_sub(1)
+            .and_then(|previous| members.get(previous))
+            .map(link),
+        next: members.get(index + 1).map(link),
+    }))
+}
@@ -0,0 +1,181 @@
+use crate::config::SiteConfig;
+use crate::services::feed::xml_escape;
+use crate::services::post::LISTED_POST_SQL as LISTED_POST;
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use sea_orm::{DatabaseConnection, DbBackend, DbErr, FromQueryResult, Statement};
+
+/// Protocol limit on URLs per sitemap file.
+pub const URLS_PER_SITEMAP: i64 = 50_000;
+
+#[derive(Clone, Copy)]
+pub enum SitemapKind {
+    Posts,
+    Tags,
+    Users,
+}
+
+impl SitemapKind {
+    pub const ALL: [SitemapKind; 3] = [Self::Posts, Self::Tags, Self::Users];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::Tags => "tags",
+            Self::Users => "users",
+        }
+    }
+
+    /// Every URL of this kind as `(path, lastmod)` rows in a stable order, so pages are
+    /// cut at the same place on every request.
+    fn source_sql(self) -> String {
+        match self {
+            Self::Posts => format!(
+                "SELECT '/' || u.username || '/' || p.slug AS path, \
+                 COALESCE(p.updated_at, p.created_at) AS lastmod, p.id::text AS sort_key \
+                 FROM posts p INNER JOIN users u ON u.id = p.created_by \
+                 WHERE {LISTED_POST} AND u.deleted_at IS NULL AND u.username IS NOT NULL"
+            ),
+            Self::Tags => format!(
+                "SELECT '/tags/' || t.name AS path, \
+                 MAX(COALESCE(p.updated_at, p.created_at)) AS lastmod, t.name AS sort_key \
+                 FROM tags t INNER JOIN posts_to_tags ptt ON ptt.tag_id = t.id \
+                 INNER JOIN posts p ON p.id = ptt.post_id \
+                 WHERE {LISTED_POST} GROUP BY t.id, t.name"
+            ),
+            Self::Users => "SELECT '/' || u.username AS path, \
+                 COALESCE(u.updated_at, u.created_at) AS lastmod, u.id::text AS sort_key \
+                 FROM users u WHERE u.deleted_at IS NULL AND u.username IS NOT NULL"
+                .to_string(),
+        }
+    }
+}
+
+#[derive(FromQueryResult)]
+struct UrlRow {
+    path: String,
+    lastmod: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(FromQueryResult)]
+struct PageRow {
+    page: i64,
+    lastmod: Option<DateTime<FixedOffset>>,
+}
+
+fn lastmod(value: Option<DateTime<FixedOffset>>) -> Option<String> {
+    value.map(|value| {
+        value
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    })
+}
+
+/// Percent-encodes everything outside the URL path characters we generate ourselves.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub fn sitemap_path(kind: SitemapKind, page: i64) -> String {
+    format!("/api/sitemaps/{}/{}", kind.as_str(), page)
+}
+
+/// Sitemap index listing one file per 50k URLs of each kind, with each file's newest
+/// `lastmod`.
+pub async fn index(db: &DatabaseConnection, site: &SiteConfig) -> Result<String, DbErr> {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+
+    for kind in SitemapKind::ALL {
+        let pages = PageRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "SELECT page, MAX(lastmod) AS lastmod FROM ( \
+                 SELECT ((ROW_NUMBER() OVER (ORDER BY sort_key) - 1) / $1 + 1)::bigint AS page, \
+                 lastmod FROM ({}) source) numbered GROUP BY page ORDER BY page",
+                kind.source_sql()
+            ),
+            [URLS_PER_SITEMAP.into()],
+        ))
+        .all(db)
+        .await?;
+
+        for page in pages {
+            xml.push_str("<sitemap>");
+            xml.push_str(&format!(
+                "<loc>{}</loc>",
+                xml_escape(&site.url(&sitemap_path(kind, page.page)))
+            ));
+            if let Some(lastmod) = lastmod(page.lastmod) {
+                xml.push_str(&format!("<lastmod>{}</lastmod>", lastmod));
+            }
+            xml.push_str("</sitemap>");
+        }
+    }
+
+    xml.push_str("</sitemapindex>");
+    Ok(xml)
+}
+
+/// One `urlset` file. Returns `None` for pages past the end.
+pub async fn urlset(
+    db: &DatabaseConnection,
+    site: &SiteConfig,
+    kind: SitemapKind,
+    page: i64,
+) -> Result<Option<String>, DbErr> {
+    let rows = UrlRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            "SELECT path, lastmod FROM ({}) source ORDER BY sort_key LIMIT $1 OFFSET $2",
+            kind.source_sql()
+        ),
+        [
+            URLS_PER_SITEMAP.into(),
+            ((page.max(1) - 1) * URLS_PER_SITEMAP).into(),
+        ],
+    ))
+    .all(db)
+    .await?;
+    if rows.is_empty() && page > 1 {
+        return Ok(None);
+    }
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for row in rows {
+        xml.push_str("<url>");
+        xml.push_str(&format!(
+            "<loc>{}</loc>",
+            xml_escape(&site.url(&encode_path(&row.path)))
+        ));
+        if let Some(lastmod) = lastmod(row.lastmod) {
+            xml.push_str(&format!("<lastmod>{}</lastmod>", lastmod));
+        }
+        xml.push_str("</url>");
+    }
+    xml.push_str("</urlset>");
+    Ok(Some(xml))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_path;
+
+    #[test]
+    fn encode_path_keeps_path_characters_and_escapes_the_rest() {
+        assert_eq!(
+            encode_path("/tags/c++ & rust"),
+            "/tags/c%2B%2B%20%26%20rust"
+        );
+        assert_eq!(encode_path("/jane_doe/my-post"), "/jane_doe/my-post");
+    }
+}
@@ -0,0 +1,160 @@
+use crate::entities::{post_slug_history, posts, username_history, users};
+use crate::models::redirect::RedirectResponse;
+use crate::services::post::is_live;
+use crate::services::visibility::PostVisibility;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+};
+use uuid::Uuid;
+
+/// Remembers `old_slug` for the post after a slug change. Taking a slug back drops it from the
+/// history so the post never redirects to itself.
+pub async fn record_post_slug<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    old_slug: &str,
+    new_slug: &str,
+) -> Result<(), DbErr> {
+    if old_slug == new_slug {
+        return Ok(());
+    }
+    post_slug_history::Entity::delete_many()
+        .filter(post_slug_history::Column::PostId.eq(post_id))
+        .filter(post_slug_history::Column::Slug.is_in([old_slug, new_slug]))
+        .exec(conn)
+        .await?;
+    post_slug_history::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        post_id: Set(post_id),
+        slug: Set(old_slug.to_string()),
+        created_at: Set(Utc::now().into()),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// Same as [`record_post_slug`] for a renamed user.
+pub async fn record_username<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Uuid,
+    old_username: &str,
+    new_username: &str,
+) -> Result<(), DbErr> {
+    if old_username == new_username {
+        return Ok(());
+    }
+    username_history::Entity::delete_many()
+        .filter(username_history::Column::UserId.eq(user_id))
+        .filter(username_history::Column::Username.is_in([old_username, new_username]))
+        .exec(conn)
+        .await?;
+    username_history::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        username: Set(old_username.to_string()),
+        created_at: Set(Utc::now().into()),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// The user currently holding `username`, or else the one who most recently gave it up.
+/// A current holder always wins, so a reclaimed name stops redirecting.
+async fn resolve_user(
+    db: &DatabaseConnection,
+    username: &str,
+) -> Result<Option<users::Model>, DbErr> {
+    let current = users::Entity::find()
+        .filter(users::Column::Username.eq(username))
+        .filter(users::Column::DeletedAt.is_null())
+        .one(db)
+        .await?;
+    if current.is_some() {
+        return Ok(current);
+    }
+    users::Entity::find()
+        .join(JoinType::InnerJoin, users::Relation::UsernameHistory.def())
+        .filter(username_history::Column::Username.eq(username))
+        .filter(users::Column::DeletedAt.is_null())
+        .order_by_desc(username_history::Column::CreatedAt)
+        .one(db)
+        .await
+}
+
+/// A live, non-private post by `user` under `slug`, looking at current slugs before old ones.
+async fn resolve_post(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    slug: &str,
+) -> Result<Option<posts::Model>, DbErr> {
+    let current = posts::Entity::find()
+        .filter(posts::Column::CreatedBy.eq(user_id))
+        .filter(posts::Column::Slug.eq(slug))
+        .filter(is_live())
+        .filter(posts::Column::Visibility.ne(PostVisibility::Private.as_str()))
+        .one(db)
+        .await?;
+    if current.is_some() {
+        return Ok(current);
+    }
+    posts::Entity::find()
+        .join(JoinType::InnerJoin, posts::Relation::PostSlugHistory.def())
+        .filter(posts::Column::CreatedBy.eq(user_id))
+        .filter(post_slug_history::Column::Slug.eq(slug))
+        .filter(is_live())
+        .filter(posts::Column::Visibility.ne(PostVisibility::Private.as_str()))
+        .order_by_desc(post_slug_history::Column::CreatedAt)
+        .one(db)
+        .await
+}
+
+fn post_location(username: &str, slug: &str) -> String {
+    format!("/api/posts/u/{username}/{slug}")
+}
+
+/// Canonical location of the post a `/api/posts/u/{username}/{slug}` link refers to, following
+/// renamed users and changed slugs. `moved` is false when the link is already canonical.
+pub async fn resolve_post_location(
+    db: &DatabaseConnection,
+    username: &str,
+    slug: &str,
+) -> Result<Option<RedirectResponse>, DbErr> {
+    let Some(user) = resolve_user(db, username).await? else {
+        return Ok(None);
+    };
+    let Some(current_username) = user.username else {
+        return Ok(None);
+    };
+    let Some(post) = resolve_post(db, user.id, slug).await? else {
+        return Ok(None);
+    };
+    Ok(Some(RedirectResponse {
+        moved: current_username != username || post.slug != slug,
+        location: post_location(&current_username, &post.slug),
+        username: current_username,
+        slug: Some(post.slug),
+    }))
+}
+
+/// Canonical location of a renamed user's profile.
+pub async fn resolve_user_location(
+    db: &DatabaseConnection,
+    username: &str,
+) -> Result<Option<RedirectResponse>, DbErr> {
+    let Some(current_username) = resolve_user(db, username)
+        .await?
+        .and_then(|user| user.username)
+    else {
+        return Ok(None);
+    };
+    Ok(Some(RedirectResponse {
+        moved: current_username != username,
+        location: format!("/api/users/username/{current_username}"),
+        username: current_username,
+        slug: None,
+    }))
+}
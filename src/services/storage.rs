@@ -0,0 +1,142 @@
+use crate::config::{StorageBackend, StorageConfig};
+use bytes::Bytes;
+use object_store::{
+    ObjectStore, aws::AmazonS3Builder, local::LocalFileSystem, path::Path as ObjectPath,
+};
+use std::sync::{Arc, OnceLock};
+
+static STORAGE: OnceLock<Storage> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound,
+    Backend(object_store::Error),
+}
+
+impl From<object_store::Error> for StorageError {
+    fn from(err: object_store::Error) -> Self {
+        match err {
+            object_store::Error::NotFound { .. } => Self::NotFound,
+            err => Self::Backend(err),
+        }
+    }
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "object not found"),
+            Self::Backend(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// Uploaded files, kept on the local filesystem or in an S3-compatible bucket. Keys are
+/// `/`-separated paths such as `avatar/<user>/<upload>/thumb.jpg`.
+pub struct Storage {
+    store: Arc<dyn ObjectStore>,
+    public_base_url: Option<String>,
+    max_upload_bytes: usize,
+}
+
+impl Storage {
+    fn from_config(cfg: &StorageConfig) -> Result<Self, StorageError> {
+        let store: Arc<dyn ObjectStore> = match &cfg.backend {
+            StorageBackend::Local { root } => {
+                std::fs::create_dir_all(root).map_err(|err| {
+                    StorageError::Backend(object_store::Error::Generic {
+                        store: "LocalFileSystem",
+                        source: Box::new(err),
+                    })
+                })?;
+                Arc::new(LocalFileSystem::new_with_prefix(root)?)
+            }
+            StorageBackend::S3 {
+                bucket,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+            } => {
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_region(region);
+                if let Some(endpoint) = endpoint {
+                    builder = builder
+                        .with_endpoint(endpoint)
+                        .with_allow_http(endpoint.starts_with("http://"))
+                        .with_virtual_hosted_style_request(false);
+                }
+                if let Some(access_key_id) = access_key_id {
+                    builder = builder.with_access_key_id(access_key_id);
+                }
+                if let Some(secret_access_key) = secret_access_key {
+                    builder = builder.with_secret_access_key(secret_access_key);
+                }
+                Arc::new(builder.build()?)
+            }
+        };
+        Ok(Self {
+            store,
+            public_base_url: cfg.public_base_url.clone(),
+            max_upload_bytes: cfg.max_upload_bytes,
+        })
+    }
+
+    pub fn init(cfg: &StorageConfig) -> Result<(), StorageError> {
+        let storage = Self::from_config(cfg)?;
+        if STORAGE.set(storage).is_err() {
+            panic!("Storage already initialized");
+        }
+        Ok(())
+    }
+
+    pub fn get() -> &'static Storage {
+        STORAGE.get().expect("Storage not initialized")
+    }
+
+    pub fn max_upload_bytes(&self) -> usize {
+        self.max_upload_bytes
+    }
+
+    pub async fn put(&self, key: &str, data: Bytes) -> Result<(), StorageError> {
+        self.store.put(&ObjectPath::from(key), data.into()).await?;
+        Ok(())
+    }
+
+    pub async fn read(&self, key: &str) -> Result<Bytes, StorageError> {
+        Ok(self
+            .store
+            .get(&ObjectPath::from(key))
+            .await?
+            .bytes()
+            .await?)
+    }
+
+    /// Deleting a missing key is not an error.
+    pub async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match self.store.delete(&ObjectPath::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Public URL for `key`, suitable for `posts.photo_url` and `users.image`.
+    pub fn url(&self, key: &str) -> String {
+        match &self.public_base_url {
+            Some(base_url) => format!("{base_url}/{key}"),
+            None => crate::config::SiteConfig::get().url(&format!("/api/uploads/files/{key}")),
+        }
+    }
+}
+
+/// Content type of a stored file, from the extension `uploads` gives every key.
+pub fn content_type_for_key(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("jpg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
@@ -1,6 +1,12 @@
-use crate::entities::{posts, tags};
-use crate::models::tag::{SitemapTag, Tag};
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryOrder, QuerySelect};
+use crate::entities::{posts, tag_follows, tags};
+use crate::models::tag::{SitemapTag, Tag, TagFollowResponse};
+use crate::services::visibility::PostVisibility;
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, sea_query::OnConflict,
+};
+use uuid::Uuid;
 
 pub async fn get_tag_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<Tag>, DbErr> {
     let tag = tags::Entity::find_by_id(id).one(db).await?;
@@ -17,9 +23,11 @@ pub async fn get_tags_for_sitemap(
         .await?
         .into_iter()
         .filter(|(_, posts)| {
-            posts
-                .iter()
-                .any(|post| post.published.unwrap_or(false) && post.deleted_at.is_none())
+            posts.iter().any(|post| {
+                post.published.unwrap_or(false)
+                    && post.deleted_at.is_none()
+                    && post.visibility == PostVisibility::Public.as_str()
+            })
         })
         .map(|(tag, _)| tag)
         .take(limit.max(0) as usize)
@@ -44,3 +52,64 @@ pub async fn get_all_tags(
 
     Ok((tag_models.into_iter().map(Into::into).collect(), total))
 }
+
+/// Idempotent; returns `None` when the tag does not exist.
+pub async fn follow_tag(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    tag_id: i32,
+) -> Result<Option<TagFollowResponse>, DbErr> {
+    let Some(tag) = tags::Entity::find_by_id(tag_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    tag_follows::Entity::insert(tag_follows::ActiveModel {
+        user_id: Set(user_id),
+        tag_id: Set(tag.id),
+        created_at: Set(Utc::now().into()),
+    })
+    .on_conflict(
+        OnConflict::columns([tag_follows::Column::UserId, tag_follows::Column::TagId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+
+    Ok(Some(TagFollowResponse {
+        tag: tag.into(),
+        following: true,
+    }))
+}
+
+pub async fn unfollow_tag(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    tag_id: i32,
+) -> Result<Option<TagFollowResponse>, DbErr> {
+    let Some(tag) = tags::Entity::find_by_id(tag_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    tag_follows::Entity::delete_many()
+        .filter(tag_follows::Column::UserId.eq(user_id))
+        .filter(tag_follows::Column::TagId.eq(tag.id))
+        .exec(db)
+        .await?;
+
+    Ok(Some(TagFollowResponse {
+        tag: tag.into(),
+        following: false,
+    }))
+}
+
+pub async fn get_followed_tags(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<Tag>, DbErr> {
+    let tags = tags::Entity::find()
+        .inner_join(tag_follows::Entity)
+        .filter(tag_follows::Column::UserId.eq(user_id))
+        .order_by_asc(tags::Column::Name)
+        .all(db)
+        .await?;
+    Ok(tags.into_iter().map(Into::into).collect())
+}
@@ -0,0 +1,123 @@
+use crate::entities::posts;
+use crate::models::post::Post;
+use crate::pagination::{Cursor, Page, Paged};
+use crate::services::post::{LIVE_POST_SQL, hydrate_posts};
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    Statement,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum TimelineError {
+    Db(DbErr),
+    /// The cursor does not come from a timeline page.
+    InvalidCursor,
+}
+
+impl From<DbErr> for TimelineError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+#[derive(FromQueryResult)]
+struct ScoredRow {
+    id: Uuid,
+    score: f64,
+}
+
+/// Posts by followed authors or carrying a followed tag, ranked by a hot score: log10 of
+/// likes + bookmarks + comments plus publish time in units of 12.5 hours, so a post needs
+/// ten times the engagement to outrank one published 12.5 hours later.
+fn timeline_sql() -> String {
+    format!(
+        "WITH candidates AS ( \
+        SELECT p.id, COALESCE(p.published_at, p.created_at) AS live_at FROM posts p \
+        WHERE {LIVE_POST_SQL} \
+        AND COALESCE(p.published_at, p.created_at) <= $2 \
+        AND p.created_by <> $1 \
+        AND (p.visibility = 'public' OR (p.visibility = 'followers' AND p.created_by IN ( \
+                SELECT following_id FROM user_follows \
+                WHERE follower_id = $1 AND deleted_at IS NULL))) \
+        AND (p.created_by IN ( \
+                SELECT following_id FROM user_follows \
+                WHERE follower_id = $1 AND deleted_at IS NULL) \
+            OR EXISTS ( \
+                SELECT 1 FROM posts_to_tags ptt \
+                INNER JOIN tag_follows tf ON tf.tag_id = ptt.tag_id AND tf.user_id = $1 \
+                WHERE ptt.post_id = p.id)) \
+    ), scored AS ( \
+        SELECT c.id, (LOG(1 \
+            + (SELECT COUNT(*) FROM post_likes l \
+               WHERE l.post_id = c.id AND l.deleted_at IS NULL AND l.created_at <= $2) \
+            + (SELECT COUNT(*) FROM post_bookmarks b \
+               WHERE b.post_id = c.id AND b.created_at <= $2) \
+            + (SELECT COUNT(*) FROM post_comments pc \
+               WHERE pc.post_id = c.id AND pc.deleted_at IS NULL AND pc.created_at <= $2)) \
+            + EXTRACT(EPOCH FROM c.live_at) / 45000.0)::float8 AS score \
+        FROM candidates c \
+    ) \
+    SELECT id, score FROM scored \
+    WHERE $3::float8 IS NULL OR (score, id) < ($3::float8, $4::uuid) \
+    ORDER BY score DESC, id DESC \
+    LIMIT $5"
+    )
+}
+
+/// One page of `user_id`'s timeline. Ranking is fixed when the first page is served: the
+/// cursor carries that moment, and later pages only consider posts and engagement up to it,
+/// so scores cannot move between pages and nothing is skipped or repeated.
+pub async fn get_timeline(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    page: &Page,
+) -> Result<Paged<Post>, TimelineError> {
+    let position = match page {
+        Page::After { cursor, .. } => {
+            Some(cursor.rank_position().ok_or(TimelineError::InvalidCursor)?)
+        }
+        Page::Offset { .. } => None,
+    };
+    let as_of = position.map_or_else(Utc::now, |(as_of, _, _)| as_of);
+    let limit = page.limit().max(1);
+
+    let mut rows = ScoredRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        timeline_sql(),
+        [
+            user_id.into(),
+            as_of.into(),
+            position.map(|(_, score, _)| score).into(),
+            position.map(|(_, _, id)| id).into(),
+            (limit + 1).into(),
+        ],
+    ))
+    .all(db)
+    .await?;
+
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next_cursor = rows
+        .last()
+        .filter(|_| has_more)
+        .map(|row| Cursor::ranked(as_of, row.score, row.id).encode());
+
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let mut by_id: HashMap<Uuid, posts::Model> = posts::Entity::find()
+        .filter(posts::Column::Id.is_in(ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|post| (post.id, post))
+        .collect();
+    let ordered = ids.iter().filter_map(|id| by_id.remove(id)).collect();
+
+    Ok(Paged {
+        items: hydrate_posts(db, ordered, true).await?,
+        total: None,
+        next_cursor,
+    })
+}
@@ -0,0 +1,121 @@
+use crate::entities::{post_trending_scores, posts};
+use crate::models::post::Post;
+use crate::services::post::{LISTED_POST_SQL, hydrate_posts, is_listed};
+use crate::services::post_view::COUNTED_VIEW_SQL;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Statement, TransactionTrait,
+};
+use serde::Deserialize;
+
+/// Engagement weights: a comment or like says more about a post than a view does.
+const VIEW_WEIGHT: f64 = 1.0;
+const LIKE_WEIGHT: f64 = 2.0;
+const BOOKMARK_WEIGHT: f64 = 2.0;
+const COMMENT_WEIGHT: f64 = 3.0;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrendingWindow {
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl TrendingWindow {
+    pub const ALL: [TrendingWindow; 3] = [Self::Day, Self::Week, Self::Month];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "24h",
+            Self::Week => "7d",
+            Self::Month => "30d",
+        }
+    }
+
+    fn hours(self) -> i32 {
+        match self {
+            Self::Day => 24,
+            Self::Week => 24 * 7,
+            Self::Month => 24 * 30,
+        }
+    }
+}
+
+/// Weighted views, likes, bookmarks and comments from inside the window, divided by
+/// `(hours since publish + 2) ^ gravity` so a post has to keep earning engagement to stay up.
+fn refresh_sql() -> String {
+    format!(
+        "WITH events AS ( \
+            SELECT v.post_id, {VIEW_WEIGHT}::float8 AS weight FROM post_views v \
+            WHERE {COUNTED_VIEW_SQL} AND v.created_at >= NOW() - make_interval(hours => $2) \
+            UNION ALL \
+            SELECT post_id, {LIKE_WEIGHT}::float8 FROM post_likes \
+            WHERE deleted_at IS NULL AND created_at >= NOW() - make_interval(hours => $2) \
+            UNION ALL \
+            SELECT post_id, {BOOKMARK_WEIGHT}::float8 FROM post_bookmarks \
+            WHERE created_at >= NOW() - make_interval(hours => $2) \
+            UNION ALL \
+            SELECT post_id, {COMMENT_WEIGHT}::float8 FROM post_comments \
+            WHERE deleted_at IS NULL AND created_at >= NOW() - make_interval(hours => $2) \
+        ), points AS ( \
+            SELECT post_id, SUM(weight) AS points FROM events GROUP BY post_id \
+        ) \
+        INSERT INTO post_trending_scores (post_id, time_window, score, computed_at) \
+        SELECT p.id, $1, (pt.points / POWER(GREATEST(EXTRACT(EPOCH FROM \
+            NOW() - COALESCE(p.published_at, p.created_at)) / 3600.0, 0) + 2, $3))::float8, NOW() \
+        FROM points pt INNER JOIN posts p ON p.id = pt.post_id \
+        WHERE {LISTED_POST_SQL}"
+    )
+}
+
+/// Replaces the ranking for `window` in one transaction, so readers never see it half built.
+/// Returns the number of ranked posts.
+pub async fn refresh(
+    db: &DatabaseConnection,
+    window: TrendingWindow,
+    gravity: f64,
+) -> Result<u64, DbErr> {
+    let txn = db.begin().await?;
+    post_trending_scores::Entity::delete_many()
+        .filter(post_trending_scores::Column::TimeWindow.eq(window.as_str()))
+        .exec(&txn)
+        .await?;
+    let result = txn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            refresh_sql(),
+            [
+                window.as_str().into(),
+                window.hours().into(),
+                gravity.into(),
+            ],
+        ))
+        .await?;
+    txn.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// Highest-ranked live posts for `window` as of the last refresh.
+pub async fn get_trending_posts(
+    db: &DatabaseConnection,
+    window: TrendingWindow,
+    limit: i64,
+) -> Result<Vec<Post>, DbErr> {
+    let post_models = posts::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            post_trending_scores::Relation::Posts.def().rev(),
+        )
+        .filter(post_trending_scores::Column::TimeWindow.eq(window.as_str()))
+        .filter(is_listed())
+        .order_by_desc(post_trending_scores::Column::Score)
+        .order_by_desc(posts::Column::CreatedAt)
+        .limit(limit.max(0) as u64)
+        .all(db)
+        .await?;
+
+    hydrate_posts(db, post_models, true).await
+}
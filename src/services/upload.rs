@@ -0,0 +1,285 @@
+use crate::entities::uploads;
+use crate::models::upload::{StoredVariant, UploadResponse};
+use crate::services::storage::{Storage, StorageError};
+use bytes::Bytes;
+use chrono::Utc;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, imageops::FilterType};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait, Set};
+use serde::Deserialize;
+use std::io::Cursor;
+use uuid::Uuid;
+
+/// Largest width or height accepted, so a tiny file cannot decode into a huge bitmap.
+const MAX_DIMENSION: u32 = 8192;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum UploadError {
+    Db(DbErr),
+    Storage(StorageError),
+    UnsupportedType,
+    InvalidImage(String),
+    UploadNotFound,
+    NotOwner,
+}
+
+impl From<DbErr> for UploadError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
+impl From<StorageError> for UploadError {
+    fn from(err: StorageError) -> Self {
+        Self::Storage(err)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadPurpose {
+    Avatar,
+    PostCover,
+}
+
+/// How a variant is sized: avatars are cropped to a square, covers keep their aspect ratio.
+#[derive(Clone, Copy)]
+enum Resize {
+    Square(u32),
+    FitWidth(u32),
+}
+
+impl UploadPurpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Avatar => "avatar",
+            Self::PostCover => "post_cover",
+        }
+    }
+
+    fn variants(self) -> &'static [(&'static str, Resize)] {
+        match self {
+            Self::Avatar => &[
+                ("thumb", Resize::Square(64)),
+                ("medium", Resize::Square(256)),
+            ],
+            Self::PostCover => &[
+                ("thumb", Resize::FitWidth(320)),
+                ("medium", Resize::FitWidth(960)),
+                ("large", Resize::FitWidth(1600)),
+            ],
+        }
+    }
+}
+
+/// Detects the image type from its magic bytes; the client's declared content type is ignored.
+pub fn sniff_format(data: &[u8]) -> Result<ImageFormat, UploadError> {
+    match image::guess_format(data) {
+        Ok(
+            format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP),
+        ) => Ok(format),
+        _ => Err(UploadError::UnsupportedType),
+    }
+}
+
+fn extension(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Png => "png",
+        ImageFormat::Gif => "gif",
+        _ => "webp",
+    }
+}
+
+struct EncodedVariant {
+    name: &'static str,
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    data: Bytes,
+}
+
+struct ProcessedImage {
+    width: u32,
+    height: u32,
+    variants: Vec<EncodedVariant>,
+}
+
+fn resize(image: &DynamicImage, resize: Resize) -> DynamicImage {
+    match resize {
+        Resize::Square(size) => {
+            let size = size.min(image.width()).min(image.height());
+            image.resize_to_fill(size, size, FilterType::Lanczos3)
+        }
+        // Never upscale: a narrow original is re-encoded at its own size.
+        Resize::FitWidth(width) if image.width() <= width => image.clone(),
+        Resize::FitWidth(width) => image.resize(width, u32::MAX, FilterType::Lanczos3),
+    }
+}
+
+/// Variants are PNG when the source has transparency and JPEG otherwise.
+fn encode(image: DynamicImage) -> Result<(ImageFormat, Bytes), image::ImageError> {
+    let mut buf = Cursor::new(Vec::new());
+    let format = if image.color().has_alpha() {
+        image.write_to(&mut buf, ImageFormat::Png)?;
+        ImageFormat::Png
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8()).write_to(&mut buf, ImageFormat::Jpeg)?;
+        ImageFormat::Jpeg
+    };
+    Ok((format, Bytes::from(buf.into_inner())))
+}
+
+fn process(
+    data: &[u8],
+    format: ImageFormat,
+    purpose: UploadPurpose,
+) -> Result<ProcessedImage, image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    let mut variants = Vec::with_capacity(purpose.variants().len());
+    for &(name, size) in purpose.variants() {
+        let resized = resize(&image, size);
+        let (width, height) = (resized.width(), resized.height());
+        let (format, data) = encode(resized)?;
+        variants.push(EncodedVariant {
+            name,
+            width,
+            height,
+            format,
+            data,
+        });
+    }
+    Ok(ProcessedImage {
+        width: image.width(),
+        height: image.height(),
+        variants,
+    })
+}
+
+/// Validates and stores an image with its resized variants under
+/// `{purpose}/{user_id}/{upload_id}/`.
+pub async fn create_upload(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    purpose: UploadPurpose,
+    data: Bytes,
+) -> Result<UploadResponse, UploadError> {
+    let format = sniff_format(&data)?;
+    let processed = {
+        let data = data.clone();
+        tokio::task::spawn_blocking(move || process(&data, format, purpose))
+            .await
+            .map_err(|err| UploadError::InvalidImage(err.to_string()))?
+            .map_err(|err| UploadError::InvalidImage(err.to_string()))?
+    };
+
+    let storage = Storage::get();
+    let id = Uuid::new_v4();
+    let prefix = format!("{}/{}/{}", purpose.as_str(), user_id, id);
+    let original_key = format!("{prefix}/original.{}", extension(format));
+    let byte_size = data.len() as i64;
+
+    let mut written = vec![original_key.clone()];
+    storage.put(&original_key, data).await?;
+    let mut stored = Vec::with_capacity(processed.variants.len());
+    for variant in processed.variants {
+        let key = format!("{prefix}/{}.{}", variant.name, extension(variant.format));
+        if let Err(err) = storage.put(&key, variant.data).await {
+            remove_objects(storage, &written).await;
+            return Err(err.into());
+        }
+        written.push(key.clone());
+        stored.push(StoredVariant {
+            name: variant.name.to_string(),
+            key,
+            width: variant.width,
+            height: variant.height,
+        });
+    }
+
+    let upload = uploads::ActiveModel {
+        id: Set(id),
+        user_id: Set(user_id),
+        purpose: Set(purpose.as_str().to_string()),
+        content_type: Set(format.to_mime_type().to_string()),
+        byte_size: Set(byte_size),
+        width: Set(processed.width as i32),
+        height: Set(processed.height as i32),
+        storage_key: Set(original_key),
+        variants: Set(serde_json::to_string(&stored).unwrap_or_else(|_| "[]".to_string())),
+        created_at: Set(Utc::now().into()),
+    };
+    match upload.insert(db).await {
+        Ok(model) => Ok(UploadResponse::from_entity(model, storage)),
+        Err(err) => {
+            remove_objects(storage, &written).await;
+            Err(err.into())
+        }
+    }
+}
+
+async fn remove_objects(storage: &Storage, keys: &[String]) {
+    for key in keys {
+        if let Err(err) = storage.delete(key).await {
+            tracing::warn!("Failed to remove stored object {}: {}", key, err);
+        }
+    }
+}
+
+pub async fn get_upload(db: &DatabaseConnection, id: Uuid) -> Result<UploadResponse, UploadError> {
+    let upload = uploads::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(UploadError::UploadNotFound)?;
+    Ok(UploadResponse::from_entity(upload, Storage::get()))
+}
+
+/// Deletes the row and every stored object. URLs already saved in posts or profiles stop
+/// resolving.
+pub async fn delete_upload(
+    db: &DatabaseConnection,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<(), UploadError> {
+    let upload = uploads::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(UploadError::UploadNotFound)?;
+    if upload.user_id != user_id {
+        return Err(UploadError::NotOwner);
+    }
+
+    let mut keys = vec![upload.storage_key.clone()];
+    keys.extend(
+        serde_json::from_str::<Vec<StoredVariant>>(&upload.variants)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|variant| variant.key),
+    );
+    upload.delete(db).await?;
+    remove_objects(Storage::get(), &keys).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_format_from_contents() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(sniff_format(png).ok(), Some(ImageFormat::Png));
+        assert!(matches!(
+            sniff_format(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+            Err(UploadError::UnsupportedType)
+        ));
+    }
+}
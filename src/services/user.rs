@@ -1,13 +1,26 @@
 use crate::entities::users;
 use crate::models::user::UserResponse;
-use crate::services::user_hydration;
+use crate::services::{slug_history, user_hydration};
 use chrono::Utc;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
+#[derive(Debug)]
+pub enum UserError {
+    Db(DbErr),
+    UserNotFound,
+    UsernameTaken,
+}
+
+impl From<DbErr> for UserError {
+    fn from(err: DbErr) -> Self {
+        Self::Db(err)
+    }
+}
+
 async fn hydrate_user(db: &DatabaseConnection, user: users::Model) -> Result<UserResponse, DbErr> {
     let users_by_id = user_hydration::load_user_response_map(db, [user.id]).await?;
     Ok(users_by_id
@@ -89,3 +102,44 @@ pub async fn soft_delete(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbEr
 
     Ok(true)
 }
+
+/// Renames a user, keeping the old name in `username_history` so existing profile and post
+/// links redirect to the new one. Tokens issued before the rename still carry the old name
+/// until refreshed.
+pub async fn change_username(
+    db: &DatabaseConnection,
+    id: Uuid,
+    username: &str,
+) -> Result<UserResponse, UserError> {
+    let txn = db.begin().await?;
+    let user = users::Entity::find_by_id(id)
+        .filter(users::Column::DeletedAt.is_null())
+        .one(&txn)
+        .await?
+        .ok_or(UserError::UserNotFound)?;
+    if user.username.as_deref() == Some(username) {
+        return Ok(hydrate_user(db, user).await?);
+    }
+
+    let taken = users::Entity::find()
+        .filter(users::Column::Username.eq(username))
+        .filter(users::Column::Id.ne(id))
+        .one(&txn)
+        .await?
+        .is_some();
+    if taken {
+        return Err(UserError::UsernameTaken);
+    }
+
+    let old_username = user.username.clone();
+    let mut active = user.into_active_model();
+    active.username = Set(Some(username.to_string()));
+    active.updated_at = Set(Some(Utc::now().into()));
+    let user = active.update(&txn).await?;
+    if let Some(old_username) = old_username {
+        slug_history::record_username(&txn, id, &old_username, username).await?;
+    }
+    txn.commit().await?;
+
+    Ok(hydrate_user(db, user).await?)
+}
@@ -1,11 +1,15 @@
 use crate::entities::{profiles, user_follows, users};
 use crate::models::user::UserResponse;
 use crate::models::user_follow::{FollowResponse, FollowStats};
+use crate::pagination::{self, Cursor, Page, Paged};
+use crate::services::counter;
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, JoinType,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, RelationTrait, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect, QueryTrait, RelationTrait, Select,
+    Set, Statement, TransactionTrait, prelude::Expr,
 };
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -31,8 +35,8 @@ async fn user_exists(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
         .map(|user| user.is_some())
 }
 
-pub async fn is_following(
-    db: &DatabaseConnection,
+pub async fn is_following<C: ConnectionTrait>(
+    conn: &C,
     follower_id: Uuid,
     following_id: Uuid,
 ) -> Result<bool, DbErr> {
@@ -40,7 +44,7 @@ pub async fn is_following(
         .filter(user_follows::Column::FollowerId.eq(follower_id))
         .filter(user_follows::Column::FollowingId.eq(following_id))
         .filter(user_follows::Column::DeletedAt.is_null())
-        .one(db)
+        .one(conn)
         .await
         .map(|follow| follow.is_some())
 }
@@ -58,10 +62,17 @@ pub async fn follow_user(
         return Err(UserFollowError::UserNotFound);
     }
 
-    if is_following(db, follower_id, following_id).await? {
+    let txn = db.begin().await?;
+    // Serializes concurrent follows of the same pair so only one live row is created.
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+        [format!("user_follow:{follower_id}:{following_id}").into()],
+    ))
+    .await?;
+    if is_following(&txn, follower_id, following_id).await? {
         return Err(UserFollowError::AlreadyFollowing);
     }
-
     user_follows::ActiveModel {
         id: Set(Uuid::new_v4()),
         follower_id: Set(follower_id),
@@ -70,8 +81,10 @@ pub async fn follow_user(
         updated_at: Set(Some(Utc::now().into())),
         deleted_at: Set(None),
     }
-    .insert(db)
+    .insert(&txn)
     .await?;
+    counter::adjust_follow(&txn, follower_id, following_id, 1).await?;
+    txn.commit().await?;
 
     Ok(FollowResponse {
         is_following: true,
@@ -84,20 +97,27 @@ pub async fn unfollow_user(
     follower_id: Uuid,
     following_id: Uuid,
 ) -> Result<FollowResponse, UserFollowError> {
-    let Some(follow) = user_follows::Entity::find()
+    let now = Utc::now();
+    let txn = db.begin().await?;
+    let result = user_follows::Entity::update_many()
+        .col_expr(user_follows::Column::DeletedAt, Expr::value(now))
+        .col_expr(user_follows::Column::UpdatedAt, Expr::value(now))
         .filter(user_follows::Column::FollowerId.eq(follower_id))
         .filter(user_follows::Column::FollowingId.eq(following_id))
         .filter(user_follows::Column::DeletedAt.is_null())
-        .one(db)
-        .await?
-    else {
+        .exec(&txn)
+        .await?;
+    if result.rows_affected == 0 {
         return Err(UserFollowError::NotFollowing);
-    };
-
-    let mut active: user_follows::ActiveModel = follow.into();
-    active.deleted_at = Set(Some(Utc::now().into()));
-    active.updated_at = Set(Some(Utc::now().into()));
-    active.update(db).await?;
+    }
+    counter::adjust_follow(
+        &txn,
+        follower_id,
+        following_id,
+        -(result.rows_affected as i64),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(FollowResponse {
         is_following: false,
@@ -123,68 +143,92 @@ async fn user_response(
     Ok(UserResponse::from_entity(user, profile, is_following))
 }
 
+/// Users on the other side of the follow edges in `query`, in the same order. The page is keyed
+/// on the follow row, so the cursor tracks when the follow happened.
+async fn follow_page(
+    db: &DatabaseConnection,
+    query: Select<user_follows::Entity>,
+    page: &Page,
+    other_id: fn(&user_follows::Model) -> Uuid,
+    current_user_id: Option<Uuid>,
+) -> Result<Paged<UserResponse>, DbErr> {
+    let follows = pagination::fetch_page(
+        db,
+        query,
+        (user_follows::Column::CreatedAt, user_follows::Column::Id),
+        page,
+        |follow| {
+            follow
+                .created_at
+                .map(|created_at| Cursor::new(created_at, follow.id))
+        },
+    )
+    .await?;
+
+    let mut users_by_id: HashMap<Uuid, users::Model> = users::Entity::find()
+        .filter(users::Column::Id.is_in(follows.items.iter().map(other_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    let mut responses = Vec::with_capacity(follows.items.len());
+    for follow in &follows.items {
+        if let Some(user) = users_by_id.remove(&other_id(follow)) {
+            responses.push(user_response(db, user, current_user_id).await?);
+        }
+    }
+
+    Ok(Paged {
+        items: responses,
+        total: follows.total,
+        next_cursor: follows.next_cursor,
+    })
+}
+
 pub async fn get_followers(
     db: &DatabaseConnection,
     user_id: Uuid,
-    limit: i64,
-    offset: i64,
+    page: &Page,
     current_user_id: Option<Uuid>,
-) -> Result<(Vec<UserResponse>, i64), DbErr> {
-    let query = users::Entity::find()
-        .join(
-            JoinType::InnerJoin,
-            user_follows::Relation::Follower.def().rev(),
-        )
+) -> Result<Paged<UserResponse>, DbErr> {
+    let query = user_follows::Entity::find()
+        .join(JoinType::InnerJoin, user_follows::Relation::Follower.def())
         .filter(user_follows::Column::FollowingId.eq(user_id))
         .filter(user_follows::Column::DeletedAt.is_null())
         .filter(users::Column::DeletedAt.is_null());
 
-    let total = query.clone().count(db).await? as i64;
-    let users = query
-        .order_by_desc(user_follows::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-
-    let mut responses = Vec::with_capacity(users.len());
-    for user in users {
-        responses.push(user_response(db, user, current_user_id).await?);
-    }
-
-    Ok((responses, total))
+    follow_page(
+        db,
+        query,
+        page,
+        |follow| follow.follower_id,
+        current_user_id,
+    )
+    .await
 }
 
 pub async fn get_following(
     db: &DatabaseConnection,
     user_id: Uuid,
-    limit: i64,
-    offset: i64,
+    page: &Page,
     current_user_id: Option<Uuid>,
-) -> Result<(Vec<UserResponse>, i64), DbErr> {
-    let query = users::Entity::find()
-        .join(
-            JoinType::InnerJoin,
-            user_follows::Relation::Following.def().rev(),
-        )
+) -> Result<Paged<UserResponse>, DbErr> {
+    let query = user_follows::Entity::find()
+        .join(JoinType::InnerJoin, user_follows::Relation::Following.def())
         .filter(user_follows::Column::FollowerId.eq(user_id))
         .filter(user_follows::Column::DeletedAt.is_null())
         .filter(users::Column::DeletedAt.is_null());
 
-    let total = query.clone().count(db).await? as i64;
-    let users = query
-        .order_by_desc(user_follows::Column::CreatedAt)
-        .limit(limit.max(0) as u64)
-        .offset(offset.max(0) as u64)
-        .all(db)
-        .await?;
-
-    let mut responses = Vec::with_capacity(users.len());
-    for user in users {
-        responses.push(user_response(db, user, current_user_id).await?);
-    }
-
-    Ok((responses, total))
+    follow_page(
+        db,
+        query,
+        page,
+        |follow| follow.following_id,
+        current_user_id,
+    )
+    .await
 }
 
 pub async fn get_follow_stats(
@@ -0,0 +1,165 @@
+use crate::entities::{post_share_tokens, posts, user_follows};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    prelude::Expr,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Who can read a post. Only `Public` posts appear in listings, feeds, trending and the
+/// sitemap; the author can always read their own posts.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PostVisibility {
+    #[default]
+    Public,
+    /// Readable by anyone with the URL, never listed.
+    Unlisted,
+    /// Readable by users following the author; listed only on their author page and timeline.
+    Followers,
+    /// Readable only by the author and holders of an unrevoked share token.
+    Private,
+}
+
+impl PostVisibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Followers => "followers",
+            Self::Private => "private",
+        }
+    }
+
+    /// Unknown values are treated as private so a bad row never leaks a post.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "public" => Self::Public,
+            "unlisted" => Self::Unlisted,
+            "followers" => Self::Followers,
+            _ => Self::Private,
+        }
+    }
+}
+
+/// Posts by authors `viewer` follows, as a condition on `posts.created_by`.
+fn by_followed_author(viewer: Uuid) -> Condition {
+    Condition::all().add(Expr::cust_with_values(
+        "posts.created_by IN (SELECT following_id FROM user_follows \
+         WHERE follower_id = $1 AND deleted_at IS NULL)",
+        [viewer],
+    ))
+}
+
+/// Visibility filter for listings shown to `viewer`: public posts, plus followers-only posts
+/// by authors the viewer follows or by the viewer themselves. Combine with `post::is_live`.
+pub(crate) fn listed_for(viewer: Option<Uuid>) -> Condition {
+    let public = posts::Column::Visibility.eq(PostVisibility::Public.as_str());
+    let Some(viewer) = viewer else {
+        return Condition::all().add(public);
+    };
+    Condition::any().add(public).add(
+        Condition::all()
+            .add(posts::Column::Visibility.eq(PostVisibility::Followers.as_str()))
+            .add(
+                Condition::any()
+                    .add(posts::Column::CreatedBy.eq(viewer))
+                    .add(by_followed_author(viewer)),
+            ),
+    )
+}
+
+/// Who is asking to read a post.
+#[derive(Default, Clone, Copy)]
+pub struct PostAccess<'a> {
+    pub viewer: Option<Uuid>,
+    pub share_token: Option<&'a str>,
+}
+
+impl<'a> PostAccess<'a> {
+    pub fn new(viewer: Option<Uuid>, share_token: Option<&'a str>) -> Self {
+        Self {
+            viewer,
+            share_token,
+        }
+    }
+}
+
+/// Share tokens are stored as SHA-256 hashes; the plain token is only shown once.
+pub(crate) fn hash_share_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+async fn has_share_token<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    token: &str,
+) -> Result<bool, DbErr> {
+    Ok(post_share_tokens::Entity::find()
+        .filter(post_share_tokens::Column::PostId.eq(post_id))
+        .filter(post_share_tokens::Column::TokenHash.eq(hash_share_token(token)))
+        .filter(post_share_tokens::Column::RevokedAt.is_null())
+        .count(conn)
+        .await?
+        > 0)
+}
+
+async fn follows<C: ConnectionTrait>(
+    conn: &C,
+    follower: Uuid,
+    author: Uuid,
+) -> Result<bool, DbErr> {
+    Ok(user_follows::Entity::find()
+        .filter(user_follows::Column::FollowerId.eq(follower))
+        .filter(user_follows::Column::FollowingId.eq(author))
+        .filter(user_follows::Column::DeletedAt.is_null())
+        .count(conn)
+        .await?
+        > 0)
+}
+
+/// Whether `access` may read `post` directly, by id or URL. A share token for the post also
+/// opens a followers-only post.
+pub async fn can_view<C: ConnectionTrait>(
+    conn: &C,
+    post: &posts::Model,
+    access: &PostAccess<'_>,
+) -> Result<bool, DbErr> {
+    if access.viewer == Some(post.created_by) {
+        return Ok(true);
+    }
+    let visibility = PostVisibility::parse(&post.visibility);
+    if matches!(
+        visibility,
+        PostVisibility::Public | PostVisibility::Unlisted
+    ) {
+        return Ok(true);
+    }
+    if let Some(token) = access.share_token
+        && has_share_token(conn, post.id, token).await?
+    {
+        return Ok(true);
+    }
+    match (visibility, access.viewer) {
+        (PostVisibility::Followers, Some(viewer)) => follows(conn, viewer, post.created_by).await,
+        _ => Ok(false),
+    }
+}
+
+/// The non-deleted post `post_id`, or `None` when it is missing or `access` may not read it.
+pub(crate) async fn find_viewable<C: ConnectionTrait>(
+    conn: &C,
+    post_id: Uuid,
+    access: &PostAccess<'_>,
+) -> Result<Option<posts::Model>, DbErr> {
+    let Some(post) = posts::Entity::find_by_id(post_id)
+        .filter(posts::Column::DeletedAt.is_null())
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    Ok(can_view(conn, &post, access).await?.then_some(post))
+}